@@ -1,6 +1,4 @@
-use regex::Regex;
-
-use crate::solver::Solver;
+use crate::{solver::Solver, utils::parse::regex};
 
 pub struct Day01;
 impl Solver for Day01 {
@@ -37,8 +35,8 @@ fn get_digits(s: &str) -> Option<i64> {
 }
 
 fn get_word_digits(s: &str) -> Option<i64> {
-    let r = Regex::new("one|two|three|four|five|six|seven|eight|nine|\\d").unwrap();
-    let rr = Regex::new("eno|owt|eerht|ruof|evif|xis|neves|thgie|enin|\\d").unwrap();
+    let r = regex!("one|two|three|four|five|six|seven|eight|nine|\\d");
+    let rr = regex!("eno|owt|eerht|ruof|evif|xis|neves|thgie|enin|\\d");
     let m = r.find(s).map(|m| m.as_str().to_string()).map(to_digit);
     let m2 = rr.find(&s.chars().rev().collect::<String>()).map(|m| m.as_str().chars().rev().collect::<String>()).map(to_digit);
     m.zip(m2).map(|(f, l)| f * 10 + l)
@@ -1,6 +1,9 @@
-use regex::Regex;
+use color_eyre::eyre::{bail, Result};
 
-use crate::solver::Solver;
+use crate::{
+    solver::Solver,
+    utils::parse::{self, regex},
+};
 
 pub struct Day02;
 impl Solver for Day02 {
@@ -9,6 +12,8 @@ impl Solver for Day02 {
         Some(input.split('\n')
             .filter(|s| !s.is_empty())
             .map(max_cubes)
+            .collect::<Result<Vec<_>>>().ok()?
+            .into_iter()
             .filter(|c| c.1 <= 12 && c.2 <= 13 && c.3 <= 14)
             .map(|c| c.0)
             .sum())
@@ -20,34 +25,33 @@ impl Solver for Day02 {
         Some(input.split('\n')
              .filter(|s| !s.is_empty())
              .map(max_cubes)
+             .collect::<Result<Vec<_>>>().ok()?
+             .into_iter()
              .map(|c| c.1 * c.2 * c.3)
              .sum())
     }
 }
 
-fn max_cubes(input: &str) -> (i64, i64, i64, i64) {
+fn max_cubes(input: &str) -> Result<(i64, i64, i64, i64)> {
     let mut red = 0;
     let mut green = 0;
     let mut blue = 0;
 
-    let binding = Regex::new(": |, |; ").unwrap();
-    let mut splits = binding.split(input);
-    let game_id = splits.next().unwrap()
-        .split(' ').collect::<Vec<_>>().get(1).unwrap()
-        .parse::<i64>().unwrap();
-    for count in Regex::new(": |, |; ").unwrap().split(input).skip(1) {
-        let mut it = count.split(' ');
-        let n = it.next().unwrap().parse::<i64>().unwrap();
-        let c = it.next().unwrap();
+    let [game, draws] = parse::split_n(input, ": ")?;
+    let [_, game_id] = parse::split_n(game, " ")?;
+    let game_id = game_id.parse::<i64>()?;
+    for count in regex!(", |; ").split(draws) {
+        let [n, c] = parse::split_n(count, " ")?;
+        let n = n.parse::<i64>()?;
         match c {
             "red" => red = red.max(n),
             "green" => green = green.max(n),
             "blue" => blue = blue.max(n),
-            _ => {},
+            _ => bail!("Unknown cube colour {:?}", c),
         }
     };
 
-    (game_id, red, green, blue)
+    Ok((game_id, red, green, blue))
 }
 
 #[cfg(test)]
@@ -68,7 +72,7 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"#;
     #[test]
     fn test_max_cubes() {
         let input = "Game 293: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
-        let output = max_cubes(input);
+        let output = max_cubes(input).unwrap();
         assert_eq!(output, (293, 4, 2, 6));
     }
 
@@ -1,18 +1,20 @@
 use std::collections::HashMap;
 
+use color_eyre::eyre::{eyre, Result};
 use itertools::Itertools;
 
-use crate::solver::Solver;
+use crate::{solver::Solver, utils::parse};
 
 pub struct Day04;
 impl Solver for Day04 {
     #[allow(unused)]
     fn solve1(&self, input: &str) -> Option<i64> {
-        Some(input.split('\n')
+        input.split('\n')
             .filter(|s| !s.is_empty())
             .map(split_line)
-            .map(|card| get_points(count_winning_matches(&card)))
-            .sum())
+            .map_ok(|card| get_points(count_winning_matches(&card)))
+            .sum::<Result<i64>>()
+            .ok()
     }
 
     #[allow(unused)]
@@ -20,7 +22,8 @@ impl Solver for Day04 {
         let cards = input.split('\n')
             .filter(|s| !s.is_empty())
             .map(split_line)
-            .collect_vec();
+            .collect::<Result<Vec<_>>>()
+            .ok()?;
         let mut counts: HashMap<usize, i64> = HashMap::new();
         for i in 0..cards.len() {
             let current = &cards[i];
@@ -42,37 +45,14 @@ struct Card {
     pub present: Vec<i64>,
 }
 
-fn split_line(input: &str) -> Card {
-    let (number, data) = {
-        let mut split = input.split(": ");
-        let number = split
-            .next()
-            .unwrap()
-            .split(' ')
-            .last()
-            .unwrap()
-            .parse::<i64>()
-            .unwrap();
-        let data = split.next().unwrap();
-        (number, data)
-    };
-
-    let (winning, present) = {
-        let mut split = data.split(" | ");
-        let winning = split_numbers(split.next().unwrap());
-        let present = split_numbers(split.next().unwrap());
-        (winning, present)
-    };
-
-    Card { number, winning, present }
-}
+fn split_line(input: &str) -> Result<Card> {
+    let [label, data] = parse::split_n(input, ": ")?;
+    let [winning, present] = parse::split_n(data, " | ")?;
+    let number = *parse::ints(label)?
+        .first()
+        .ok_or_else(|| eyre!("Missing card number in {:?}", input))?;
 
-fn split_numbers(input: &str) -> Vec<i64> {
-    input
-        .split(' ')
-        .filter(|s| !s.is_empty())
-        .map(|s| s.parse::<i64>().unwrap())
-        .collect_vec()
+    Ok(Card { number, winning: parse::ints(winning)?, present: parse::ints(present)? })
 }
 
 fn count_winning_matches(card: &Card) -> i64 {
@@ -106,7 +86,7 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"#;
     #[test]
     fn test_split_line() {
         let input = "Card 5: 12 55 32  3 | 12  3 90  1 7";
-        let output = split_line(input);
+        let output = split_line(input).unwrap();
         assert_eq!(output, Card { number: 5, winning: vec![12, 55, 32, 3], present: vec![12, 3, 90, 1, 7]});
     }
 
@@ -1,88 +1,179 @@
-use rayon::iter::ParallelBridge;
-use rayon::iter::ParallelIterator;
+use std::fmt::Write;
 
-use crate::solver::Solver;
+use color_eyre::eyre::{bail, eyre, Result};
+
+use crate::{
+    solver::Solver,
+    utils::{
+        interval::{Interval, IntervalSet, RangeMap},
+        parse,
+    },
+};
 
 pub struct Day05;
 impl Solver for Day05 {
     #[allow(unused)]
     fn solve1(&self, input: &str) -> Option<i64> {
-        let data = parse_sections(input);
-        data.seeds
-            .iter()
-            .map(|seed| {
-                let mut current = *seed;
-                for map in &data.maps {
-                    current = next_category(current, map);
-                }
-                (current, *seed)
-            })
-            .min_by(|a, b| a.0.cmp(&b.0))
-            .map(|(current, seed)| current.into())
+        let almanac = Almanac::new(input).ok()?;
+        almanac.seeds.iter().map(|seed| almanac.location(*seed)).min()
     }
 
     #[allow(unused)]
     fn solve2(&self, input: &str) -> Option<i64> {
-        let data = parse_sections(input);
-        data.seeds
-            .chunks(2)
-            .flat_map(|chunk| chunk[0]..(chunk[0] + chunk[1]))
-            .par_bridge()
-            .map(|seed| {
-                let mut current = seed;
-                for map in &data.maps {
-                    current = next_category(current, map);
-                }
-                (current, seed)
-            })
-            .min_by(|a, b| a.0.cmp(&b.0))
-            .map(|(current, seed)| current.into())
+        let almanac = Almanac::new(input).ok()?;
+        almanac.locations(&almanac.seed_ranges().ok()?).min()
     }
 }
 
+/// Map from one category to the next, e.g. from seed to soil.
 #[derive(Debug)]
-struct ParsedData {
+struct Stage {
+    pub from: String,
+    pub to: String,
+    pub map: RangeMap,
+}
+
+#[derive(Debug)]
+struct Almanac {
     pub seeds: Vec<i64>,
-    pub maps: Vec<Vec<(i64, i64, i64)>>,
+    /// Stages in order from seed to location, where each stage maps to the category the next one
+    /// maps from.
+    pub stages: Vec<Stage>,
 }
 
-fn parse_sections(input: &str) -> ParsedData {
-    let seeds: Vec<i64> = input
-        .lines()
-        .next()
-        .unwrap()
-        .split(": ")
-        .last()
-        .unwrap()
-        .split_whitespace()
-        .map(|s| s.parse::<i64>().unwrap())
-        .collect();
-    let maps = input.split("\n\n").into_iter().map(parse_block).skip(1).collect();
-    ParsedData { seeds, maps }
+impl Almanac {
+    fn new(input: &str) -> Result<Self> {
+        let sections = parse::sections(input)?;
+        let Some((seeds, maps)) = sections.split_first() else {
+            bail!("Almanac is empty");
+        };
+        if seeds.label != "seeds" {
+            bail!("Expected the almanac to start with seeds, got {:?}", seeds.label);
+        }
+        let seeds = parse::ints(seeds.body)?;
+        let stages = maps.iter().map(|s| parse_stage(s.label, s.body)).collect::<Result<Vec<_>>>()?;
+
+        // Every stage must pick up where the last one left off
+        match stages.first() {
+            Some(first) if first.from != "seed" => bail!("First map is from {:?}, not seed", first.from),
+            _ => {}
+        }
+        for pair in stages.windows(2) {
+            if pair[0].to != pair[1].from {
+                bail!(
+                    "{}-to-{} map is followed by {}-to-{} map",
+                    pair[0].from, pair[0].to, pair[1].from, pair[1].to
+                );
+            }
+        }
+        Ok(Self { seeds, stages })
+    }
+
+    /// Seeds read as pairs of (start, length), as in part 2.
+    fn seed_ranges(&self) -> Result<IntervalSet> {
+        let pairs = self.seeds.chunks_exact(2);
+        if !pairs.remainder().is_empty() {
+            bail!("Expected seeds in pairs of start and length, got {} seeds", self.seeds.len());
+        }
+        Ok(pairs.map(|pair| Interval::from_len(pair[0], pair[1])).collect())
+    }
+
+    fn location(&self, seed: i64) -> i64 {
+        self.stages.iter().fold(seed, |value, stage| stage.map.get(value))
+    }
+
+    /// Map whole ranges of seeds through every stage at once.
+    fn locations(&self, seeds: &IntervalSet) -> IntervalSet {
+        self.stages
+            .iter()
+            .fold(seeds.clone(), |values, stage| stage.map.apply(&values))
+    }
+
+    /// Every seed that ends up in one of `locations`.
+    #[allow(unused)]
+    fn seeds_for(&self, locations: &IntervalSet) -> IntervalSet {
+        self.stages
+            .iter()
+            .rev()
+            .fold(locations.clone(), |values, stage| stage.map.preimage(&values))
+    }
+
+    /// Convert a value from one category to a later one, e.g. from seed to humidity.
+    fn convert(&self, value: i64, from: &str, to: &str) -> Result<i64> {
+        Ok(self.trace(value, from, to)?.last().unwrap().1)
+    }
+
+    /// The value in each category passed through while converting `value` between categories,
+    /// including the starting category.
+    fn trace<'a>(&'a self, value: i64, from: &str, to: &str) -> Result<Vec<(&'a str, i64)>> {
+        let categories = self.categories();
+        let find = |name: &str| {
+            categories
+                .iter()
+                .position(|c| *c == name)
+                .ok_or_else(|| eyre!("Unknown category {:?}", name))
+        };
+        let (start, end) = (find(from)?, find(to)?);
+        if end < start {
+            bail!("Category {:?} comes before {:?}, so can't be converted to", to, from);
+        }
+        let mut current = value;
+        let mut trace = vec![(categories[start], value)];
+        for stage in &self.stages[start..end] {
+            current = stage.map.get(current);
+            trace.push((stage.to.as_str(), current));
+        }
+        Ok(trace)
+    }
+
+    /// Every category in order, from seed to location.
+    fn categories(&self) -> Vec<&str> {
+        self.stages
+            .iter()
+            .map(|s| s.from.as_str())
+            .chain(self.stages.last().map(|s| s.to.as_str()))
+            .collect()
+    }
 }
 
-fn parse_block(input: &str) -> Vec<(i64, i64, i64)> {
-    input
-        .split("\n")
-        .skip(1)
-        .filter(|s| !s.is_empty())
-        .map(|s| {
-            let mut i = s.split_whitespace();
-            (
-                i.next().unwrap().parse().unwrap(),
-                i.next().unwrap().parse().unwrap(),
-                i.next().unwrap().parse().unwrap(),
-            )
+/// Parse a stage from its `<from>-to-<to> map` label and (destination start, source start,
+/// length) lines.
+fn parse_stage(label: &str, body: &str) -> Result<Stage> {
+    let name = label
+        .strip_suffix(" map")
+        .ok_or_else(|| eyre!("Expected a map, got {:?}", label))?;
+    let [from, to] = parse::split_n(name, "-to-")?;
+    let entries = body
+        .lines()
+        .map(|line| match parse::ints(line)?[..] {
+            [dest, source, len] => Ok((dest, source, len)),
+            _ => bail!("Expected three numbers in {} map, got {:?}", name, line),
         })
-        .collect()
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Stage { from: from.to_owned(), to: to.to_owned(), map: to_range_map(&entries) })
 }
 
-fn next_category(value: i64, map: &Vec<(i64, i64, i64)>) -> i64 {
-    map.iter()
-        .filter(|m| value >= m.1 && value < (m.1 + m.2))
-        .next()
-        .map(|m| (value - m.1) + m.0)
-        .unwrap_or(value)
+/// Build a stage from its (destination start, source start, length) lines.
+fn to_range_map(entries: &[(i64, i64, i64)]) -> RangeMap {
+    let mut map = RangeMap::new();
+    for (dest, source, len) in entries {
+        map.insert(Interval::from_len(*source, *len), *dest);
+    }
+    map
+}
+
+/// Convert `value` from category `from` to `to`, optionally showing its value in every category
+/// along the way.
+pub fn describe_conversion(input: &str, value: i64, from: &str, to: &str, trace: bool) -> Result<String> {
+    let almanac = Almanac::new(input)?;
+    if !trace {
+        return Ok(format!("{} {} is {} {}\n", from, value, to, almanac.convert(value, from, to)?));
+    }
+    let mut out = String::new();
+    for (category, value) in almanac.trace(value, from, to)? {
+        writeln!(out, "{: >12} {}", category, value)?;
+    }
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -131,9 +222,89 @@ humidity-to-location map:
 
     #[test]
     fn get_next_category() {
-        assert_eq!(next_category(99, &vec![(50, 98, 2)]), 51);
-        assert_eq!(next_category(79, &vec![(50, 98, 2), (52, 50, 48)]), 81);
-        assert_eq!(next_category(47, &vec![(47, 1, 46), (1, 48, 50)]), 47);
+        assert_eq!(to_range_map(&[(50, 98, 2)]).get(99), 51);
+        assert_eq!(to_range_map(&[(50, 98, 2), (52, 50, 48)]).get(79), 81);
+        assert_eq!(to_range_map(&[(47, 1, 46), (1, 48, 50)]).get(47), 47);
+    }
+
+    #[test]
+    fn test_locations() {
+        let almanac = Almanac::new(EXAMPLE).unwrap();
+        let seeds = almanac.seed_ranges().unwrap();
+        let locations = almanac.locations(&seeds);
+        assert_eq!(locations.len(), seeds.len());
+        assert_eq!(locations.min(), Some(46));
+        for seed in [79, 82, 92, 55, 67] {
+            assert!(locations.contains(almanac.location(seed)));
+        }
+    }
+
+    #[test]
+    fn test_seeds_for() {
+        let almanac = Almanac::new(EXAMPLE).unwrap();
+        let seeds = almanac.seeds_for(&Interval::from_len(46, 1).into());
+        assert!(seeds.contains(82));
+        assert!(seeds.intervals().iter().all(|i| (i.start..i.end).all(|s| almanac.location(s) == 46)));
+        assert_eq!(seeds.intersection(&almanac.seed_ranges().unwrap()), Interval::from_len(82, 1).into());
+        assert_eq!(almanac.seeds_for(&IntervalSet::new()), IntervalSet::new());
+    }
+
+    #[test]
+    fn test_categories() {
+        let almanac = Almanac::new(EXAMPLE).unwrap();
+        assert_eq!(almanac.stages[0].from, "seed");
+        assert_eq!(almanac.stages[6].to, "location");
+        assert_eq!(almanac.convert(79, "seed", "soil").unwrap(), 81);
+        assert_eq!(almanac.convert(79, "seed", "humidity").unwrap(), 78);
+        assert_eq!(almanac.convert(79, "seed", "location").unwrap(), 82);
+        assert_eq!(almanac.convert(81, "soil", "fertilizer").unwrap(), 81);
+        assert_eq!(almanac.convert(14, "seed", "seed").unwrap(), 14);
+        assert!(almanac.convert(79, "soil", "seed").is_err());
+        assert!(almanac.convert(79, "seed", "dirt").is_err());
+        assert!(almanac.convert(79, "dirt", "soil").is_err());
+    }
+
+    #[test]
+    fn test_trace() {
+        let almanac = Almanac::new(EXAMPLE).unwrap();
+        assert_eq!(
+            almanac.trace(14, "seed", "location").unwrap(),
+            vec![
+                ("seed", 14),
+                ("soil", 14),
+                ("fertilizer", 53),
+                ("water", 49),
+                ("light", 42),
+                ("temperature", 42),
+                ("humidity", 43),
+                ("location", 43),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_describe_conversion() {
+        assert_eq!(describe_conversion(EXAMPLE, 79, "seed", "soil", false).unwrap(), "seed 79 is soil 81\n");
+        assert_eq!(
+            describe_conversion(EXAMPLE, 79, "seed", "soil", true).unwrap(),
+            "        seed 79\n        soil 81\n"
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        let broken_chain = EXAMPLE.replace("water-to-light", "fertilizer-to-light");
+        assert!(Almanac::new(&broken_chain).is_err());
+        let wrong_start = EXAMPLE.replace("seed-to-soil", "dirt-to-soil");
+        assert!(Almanac::new(&wrong_start).is_err());
+        let bad_line = EXAMPLE.replace("50 98 2", "50 98");
+        assert!(Almanac::new(&bad_line).is_err());
+        assert!(Almanac::new("").is_err());
+        // Odd seed lists still work for part 1, but can't be read as ranges
+        let odd_seeds = EXAMPLE.replace("seeds: 79 14 55 13", "seeds: 79 14 55");
+        assert!(Almanac::new(&odd_seeds).unwrap().seed_ranges().is_err());
+        assert_eq!(Day05.solve1(&odd_seeds), Some(43));
+        assert_eq!(Day05.solve2(&odd_seeds), None);
     }
 
     #[test]
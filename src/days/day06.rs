@@ -1,4 +1,8 @@
-use crate::solver::Solver;
+use std::ops::RangeInclusive;
+
+use color_eyre::eyre::Result;
+
+use crate::{solver::Solver, utils::math};
 
 pub struct Day06;
 impl Solver for Day06 {
@@ -8,18 +12,14 @@ impl Solver for Day06 {
         let times = parse_line(lines.next().unwrap());
         let distances = parse_line(lines.next().unwrap());
 
-        let product = times
+        times
             .into_iter()
-            .zip(distances.into_iter())
-            .map(|(time, distance)| {
-                find_optimal_times(time, distance)
-                    .into_iter()
-                    .count()
-                    .try_into()
-                    .unwrap()
-            })
-            .fold(1, |acc, x: i64| acc * x);
-        Some(product)
+            .zip(distances)
+            .try_fold(1i128, |product, (time, distance)| {
+                product.checked_mul(count(find_optimal_times(time, distance).ok()?))
+            })?
+            .try_into()
+            .ok()
     }
 
     #[allow(unused)]
@@ -27,41 +27,51 @@ impl Solver for Day06 {
         let mut lines = input.split("\n");
         let time = parse_line2(lines.next().unwrap());
         let distance = parse_line2(lines.next().unwrap());
-        Some(find_optimal_times(time, distance).into_iter().count().try_into().unwrap())
+        count(find_optimal_times(time, distance).ok()?).try_into().ok()
     }
 }
 
-fn parse_line(input: &str) -> Vec<i64> {
+fn parse_line(input: &str) -> Vec<i128> {
     input
         .split_whitespace()
         .skip(1)
-        .map(|s| s.parse::<i64>().unwrap())
+        .map(|s| s.parse::<i128>().unwrap())
         .collect()
 }
 
-fn parse_line2(input: &str) -> i64 {
+fn parse_line2(input: &str) -> i128 {
     input
         .split_whitespace()
         .skip(1)
         .collect::<String>()
-        .parse::<i64>().unwrap()
+        .parse::<i128>().unwrap()
 }
 
-fn calc_distance(press_time: i64, total_time: i64) -> i64 {
-    (total_time - press_time) * press_time
+/// Range of times to hold the button for to beat the record, if it can be beaten.
+/// Distance travelled is `(total_time - t) * t`, so the winning times are the integer solutions of
+/// `t^2 - total_time * t + record < 0`. Returns an error if the race is too long to solve.
+fn find_optimal_times(total_time: i128, record: i128) -> Result<Option<RangeInclusive<i128>>> {
+    let Some(times) = math::quadratic_below_zero(1, -total_time, record)? else {
+        return Ok(None);
+    };
+    // A negative record can be beaten without moving, but the button can't be held for a negative
+    // time or longer than the race.
+    let (start, end) = ((*times.start()).max(0), (*times.end()).min(total_time));
+    Ok((start <= end).then_some(start..=end))
 }
 
-fn find_optimal_times(total_time: i64, record: i64) -> Vec<i64> {
-    (0..=total_time)
-        .into_iter()
-        .filter(|t| calc_distance(*t, total_time) > record)
-        .collect()
+fn count(times: Option<RangeInclusive<i128>>) -> i128 {
+    times.map_or(0, |t| t.end() - t.start() + 1)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn calc_distance(press_time: i128, total_time: i128) -> i128 {
+        (total_time - press_time) * press_time
+    }
+
     const EXAMPLE: &str = r#"Time:      7  15   30
 Distance:  9  40  200"#;
 
@@ -81,7 +91,35 @@ Distance:  9  40  200"#;
 
     #[test]
     fn test_find_optimal_times() {
-        assert_eq!(find_optimal_times(7, 9), vec![2, 3, 4, 5]);
+        assert_eq!(find_optimal_times(7, 9).unwrap(), Some(2..=5));
+        // Holding for 10 or 20 exactly matches the record, which doesn't beat it
+        assert_eq!(find_optimal_times(30, 200).unwrap(), Some(11..=19));
+        assert_eq!(find_optimal_times(4, 4).unwrap(), None);
+        assert_eq!(find_optimal_times(5, -1).unwrap(), Some(0..=5));
+        // Same as scanning every time, for a range of races
+        for total in 0..40 {
+            for record in -5..(total * total / 4 + 2) {
+                let scanned = (0..=total).filter(|t| calc_distance(*t, total) > record).collect::<Vec<_>>();
+                let interval = find_optimal_times(total, record).unwrap().map_or(vec![], |t| t.collect());
+                assert_eq!(interval, scanned, "time {}, record {}", total, record);
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_race() {
+        let total = 3_000_000_000_000_000_000i128;
+        let record = total * total / 4 - 1;
+        // Only the exact middle beats the record
+        assert_eq!(find_optimal_times(total, record).unwrap(), Some(total / 2..=total / 2));
+        // Too long to square the race time
+        assert!(find_optimal_times(100_000_000_000_000_000_000, 1).is_err());
+        let input = "Time: 100000000000000000000\nDistance: 1";
+        assert_eq!(Day06.solve1(input), None);
+        assert_eq!(Day06.solve2(input), None);
+        // Each race is solvable, but the product of the counts overflows
+        let input = "Time: 10000000000000000000 10000000000000000000 10000000000000000000\nDistance: 0 0 0";
+        assert_eq!(Day06.solve1(input), None);
     }
 
     #[test]
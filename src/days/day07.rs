@@ -1,3 +1,6 @@
+use std::{cmp::Ordering, fmt::Write};
+
+use color_eyre::eyre::{bail, eyre, Result};
 use itertools::Itertools;
 
 use crate::solver::Solver;
@@ -6,269 +9,194 @@ pub struct Day07;
 impl Solver for Day07 {
     #[allow(unused)]
     fn solve1(&self, input: &str) -> Option<i64> {
-        let mut hands = input
-            .split("\n")
-            .filter(|s| !s.is_empty())
-            .map(Hand::new)
-            .collect::<Vec<_>>();
-        hands.sort();
-        hands.reverse();
-        Some(
-            hands
-                .iter()
-                .enumerate()
-                .map(|(i, hand)| (i as i64 + 1) * hand.bid)
-                .sum(),
-        )
+        Rules::standard().winnings(input).ok()
     }
 
     #[allow(unused)]
     fn solve2(&self, input: &str) -> Option<i64> {
-        let mut hands = input
-            .split("\n")
-            .filter(|s| !s.is_empty())
-            .map(Hand2::new)
-            .collect::<Vec<_>>();
-        hands.sort();
-        hands.reverse();
-        Some(
-            hands
-                .iter()
-                .enumerate()
-                .map(|(i, hand)| (i as i64 + 1) * hand.bid)
-                .sum(),
-        )
+        Rules::jokers().winnings(input).ok()
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
-enum CardType {
-    Ace,
-    King,
-    Queen,
-    Jack,
-    Ten,
-    Nine,
-    Eight,
-    Seven,
-    Six,
-    Five,
-    Four,
-    Three,
-    Two,
+/// How a game of Camel Cards is played.
+#[derive(Debug, Clone)]
+struct Rules {
+    /// Card labels from strongest to weakest.
+    order: Vec<char>,
+    /// Cards that act as whichever card makes the hand type strongest, while keeping their own
+    /// place in `order` when breaking ties.
+    wildcards: Vec<char>,
+    hand_size: usize,
 }
 
-impl From<char> for CardType {
-    fn from(value: char) -> Self {
-        match value {
-            'A' => Self::Ace,
-            'K' => Self::King,
-            'Q' => Self::Queen,
-            'J' => Self::Jack,
-            'T' => Self::Ten,
-            '9' => Self::Nine,
-            '8' => Self::Eight,
-            '7' => Self::Seven,
-            '6' => Self::Six,
-            '5' => Self::Five,
-            '4' => Self::Four,
-            '3' => Self::Three,
-            '2' => Self::Two,
-            _ => unimplemented!("{}", value),
-        }
-    }
-}
-
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
-enum CardType2 {
-    Ace,
-    King,
-    Queen,
-    Ten,
-    Nine,
-    Eight,
-    Seven,
-    Six,
-    Five,
-    Four,
-    Three,
-    Two,
-    Joker,
-}
-
-impl From<char> for CardType2 {
-    fn from(value: char) -> Self {
-        match value {
-            'A' => Self::Ace,
-            'K' => Self::King,
-            'Q' => Self::Queen,
-            'T' => Self::Ten,
-            '9' => Self::Nine,
-            '8' => Self::Eight,
-            '7' => Self::Seven,
-            '6' => Self::Six,
-            '5' => Self::Five,
-            '4' => Self::Four,
-            '3' => Self::Three,
-            '2' => Self::Two,
-            'J' => Self::Joker,
-            _ => unimplemented!("{}", value),
-        }
-    }
-}
-
-#[derive(Debug)]
-struct Hand {
-    cards: [CardType; 5],
-    hand_type: HandType,
-    bid: i64,
-}
-
-impl Hand {
-    fn new(input: &str) -> Self {
-        let mut parts = input.split_whitespace();
-        let cards: [CardType; 5] = parts
-            .next()
-            .unwrap()
-            .chars()
-            .map(|c| CardType::from(c))
-            .collect::<Vec<CardType>>()
-            .try_into()
-            .unwrap();
-        let hand_type: HandType = cards.into();
-        let bid = parts.next().unwrap().parse().unwrap();
+impl Rules {
+    fn new(order: &str, wildcards: &str, hand_size: usize) -> Self {
         Self {
-            cards,
-            hand_type,
-            bid,
+            order: order.chars().collect(),
+            wildcards: wildcards.chars().collect(),
+            hand_size,
         }
     }
-}
 
-impl PartialEq for Hand {
-    fn eq(&self, other: &Self) -> bool {
-        self.cards == other.cards
+    /// Part 1 rules.
+    fn standard() -> Self {
+        Self::new("AKQJT98765432", "", 5)
     }
-}
-
-impl Eq for Hand {}
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.hand_type
-            .partial_cmp(&other.hand_type)
-            .map(|ord| match ord {
-                std::cmp::Ordering::Equal => compare_card_values(self.cards, other.cards),
-                _ => ord,
-            })
+    /// Part 2 rules, where J is a joker and the weakest card.
+    fn jokers() -> Self {
+        Self::new("AKQT98765432J", "J", 5)
     }
-}
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+    /// Total winnings of every hand in the input: each bid multiplied by the hand's rank.
+    fn winnings(&self, input: &str) -> Result<i64> {
+        Ok(self
+            .ranked(input)?
+            .iter()
+            .enumerate()
+            .map(|(i, hand)| (i as i64 + 1) * hand.bid)
+            .sum())
     }
-}
 
-fn compare_card_values(a: [CardType; 5], b: [CardType; 5]) -> std::cmp::Ordering {
-    if a[0] == b[0] {
-        if a[1] == b[1] {
-            if a[2] == b[2] {
-                if a[3] == b[3] {
-                    if a[4] == b[4] {
-                        std::cmp::Ordering::Equal
-                    } else {
-                        a[4].cmp(&b[4])
-                    }
-                } else {
-                    a[3].cmp(&b[3])
-                }
-            } else {
-                a[2].cmp(&b[2])
-            }
-        } else {
-            a[1].cmp(&b[1])
-        }
-    } else {
-        a[0].cmp(&b[0])
+    /// Every hand in the input, from weakest (rank 1) to strongest.
+    fn ranked(&self, input: &str) -> Result<Vec<Hand>> {
+        let mut hands = input
+            .split("\n")
+            .filter(|s| !s.is_empty())
+            .map(|s| self.hand(s))
+            .collect::<Result<Vec<_>>>()?;
+        hands.sort();
+        hands.reverse();
+        Ok(hands)
     }
-}
-
-#[derive(Debug)]
-struct Hand2 {
-    cards: [CardType2; 5],
-    hand_type: HandType,
-    bid: i64,
-}
 
-impl Hand2 {
-    fn new(input: &str) -> Self {
+    /// Parse a hand and its bid, e.g. `32T3K 765`.
+    fn hand(&self, input: &str) -> Result<Hand> {
         let mut parts = input.split_whitespace();
-        let cards: [CardType2; 5] = parts
-            .next()
-            .unwrap()
+        let labels = parts.next().ok_or_else(|| eyre!("Hand {:?} has no cards", input))?;
+        let cards = labels
             .chars()
-            .map(|c| CardType2::from(c))
-            .collect::<Vec<CardType2>>()
-            .try_into()
-            .unwrap();
-        let hand_type: HandType = cards.into();
-        let bid = parts.next().unwrap().parse().unwrap();
-        Self {
+            .map(|c| {
+                self.order
+                    .iter()
+                    .position(|o| *o == c)
+                    .ok_or_else(|| eyre!("Card {} in hand {} isn't in the rules' card order", c, labels))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if cards.len() != self.hand_size {
+            bail!("Expected {} cards, got {}", self.hand_size, labels);
+        }
+        let wild = labels.chars().filter(|c| self.wildcards.contains(c)).count();
+        // Sizes of each group of matching cards, largest first. Wildcards always join the largest
+        // group, as that makes the strongest hand type.
+        let mut shape = labels
+            .chars()
+            .filter(|c| !self.wildcards.contains(c))
+            .counts()
+            .into_values()
+            .sorted()
+            .rev()
+            .collect_vec();
+        match shape.first_mut() {
+            Some(largest) => *largest += wild,
+            None => shape.push(wild),
+        }
+        // The card the wildcards pretend to be: the most common other card, or the strongest card
+        // if every card is wild.
+        let substitute = (wild > 0).then(|| {
+            labels
+                .chars()
+                .zip(cards.iter())
+                .filter(|(c, _)| !self.wildcards.contains(c))
+                .counts()
+                .into_iter()
+                .max_by(|((_, a_strength), a_count), ((_, b_strength), b_count)| {
+                    a_count.cmp(b_count).then(b_strength.cmp(a_strength))
+                })
+                .map_or(self.order[0], |((c, _), _)| c)
+        });
+        let bid = parts
+            .next()
+            .ok_or_else(|| eyre!("Hand {} has no bid", labels))?
+            .parse()
+            .map_err(|e| eyre!("Bid for hand {} isn't a number: {}", labels, e))?;
+        Ok(Hand {
+            labels: labels.to_owned(),
+            substitute,
             cards,
-            hand_type,
+            hand_type: HandType::from(shape.as_slice()),
+            shape,
             bid,
-        }
+        })
     }
 }
 
-impl PartialEq for Hand2 {
+/// A hand of cards. Stronger hands are ordered first, and hands are equal when they rank the same.
+#[derive(Debug, Eq)]
+struct Hand {
+    labels: String,
+    /// Card the wildcards in the hand act as, if it has any.
+    substitute: Option<char>,
+    /// Position of each card in the rules' card order, so lower is stronger.
+    cards: Vec<usize>,
+    hand_type: HandType,
+    /// Sizes of each group of matching cards after using wildcards, largest first.
+    shape: Vec<usize>,
+    bid: i64,
+}
+
+impl PartialEq for Hand {
     fn eq(&self, other: &Self) -> bool {
-        self.cards == other.cards
+        self.cmp(other) == Ordering::Equal
     }
 }
 
-impl Eq for Hand2 {}
-
-impl PartialOrd for Hand2 {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.hand_type
-            .partial_cmp(&other.hand_type)
-            .map(|ord| match ord {
-                std::cmp::Ordering::Equal => compare_card_values2(self.cards, other.cards),
-                _ => ord,
-            })
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-impl Ord for Hand2 {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+impl Ord for Hand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.hand_type
+            .cmp(&other.hand_type)
+            // Only differs from the hand type for hands larger than five cards
+            .then_with(|| other.shape.cmp(&self.shape))
+            .then_with(|| self.cards.cmp(&other.cards))
     }
 }
 
-fn compare_card_values2(a: [CardType2; 5], b: [CardType2; 5]) -> std::cmp::Ordering {
-    if a[0] == b[0] {
-        if a[1] == b[1] {
-            if a[2] == b[2] {
-                if a[3] == b[3] {
-                    if a[4] == b[4] {
-                        std::cmp::Ordering::Equal
-                    } else {
-                        a[4].cmp(&b[4])
-                    }
-                } else {
-                    a[3].cmp(&b[3])
-                }
-            } else {
-                a[2].cmp(&b[2])
-            }
-        } else {
-            a[1].cmp(&b[1])
-        }
-    } else {
-        a[0].cmp(&b[0])
+/// Table of every hand in rank order, showing how it was classified and what it won.
+pub fn explain(input: &str, part: u8) -> Result<String> {
+    let rules = match part {
+        1 => Rules::standard(),
+        2 => Rules::jokers(),
+        _ => bail!("Tried to explain a part other than 1 or 2!"),
+    };
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{: >5} | {: ^7} | {: ^12} | {: ^8} | {: >6} | {: >10}",
+        "Rank", "Hand", "Type", "Wild as", "Bid", "Winnings"
+    )?;
+    let mut total = 0;
+    for (i, hand) in rules.ranked(input)?.iter().enumerate() {
+        let winnings = (i as i64 + 1) * hand.bid;
+        total += winnings;
+        writeln!(
+            out,
+            "{: >5} | {: ^7} | {: ^12} | {: ^8} | {: >6} | {: >10}",
+            i + 1,
+            hand.labels,
+            format!("{:?}", hand.hand_type),
+            hand.substitute.map_or(String::from("-"), String::from),
+            hand.bid,
+            winnings
+        )?;
     }
+    writeln!(out, "Total winnings: {}", total)?;
+    Ok(out)
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
@@ -282,69 +210,19 @@ enum HandType {
     HighCard,
 }
 
-impl From<[CardType; 5]> for HandType {
-    fn from(value: [CardType; 5]) -> Self {
-        let unique: Vec<_> = value.iter().unique().collect();
-        if unique.len() == 1 {
-            return Self::FiveOfKind;
-        }
-        if unique.len() == 2 {
-            if value.iter().filter(|ct| *ct == unique[0]).count() == 4
-                || value.iter().filter(|ct| *ct == unique[1]).count() == 4
-            {
-                return Self::FourOfKind;
-            } else {
-                return Self::FullHouse;
-            }
-        }
-        if unique.len() == 3 {
-            if value.iter().filter(|ct| *ct == unique[0]).count() == 3
-                || value.iter().filter(|ct| *ct == unique[1]).count() == 3
-                || value.iter().filter(|ct| *ct == unique[2]).count() == 3
-            {
-                return Self::ThreeOfKind;
-            } else {
-                return Self::TwoPair;
-            }
-        }
-        if unique.len() == 4 {
-            return Self::OnePair;
-        }
-        return Self::HighCard;
-    }
-}
-
-impl From<[CardType2; 5]> for HandType {
-    fn from(value: [CardType2; 5]) -> Self {
-        let mut unique: Vec<_> = value.iter().unique().collect();
-        unique.retain(|ct| **ct != CardType2::Joker);
-        let jokers = value.iter().filter(|ct| **ct == CardType2::Joker).count();
-        if unique.len() <= 1 {
-            return Self::FiveOfKind;
-        }
-        if unique.len() == 2 {
-            if value.iter().filter(|ct| *ct == unique[0]).count() + jokers == 4
-                || value.iter().filter(|ct| *ct == unique[1]).count() + jokers == 4
-            {
-                return Self::FourOfKind;
-            } else {
-                return Self::FullHouse;
-            }
-        }
-        if unique.len() == 3 {
-            if value.iter().filter(|ct| *ct == unique[0]).count() + jokers == 3
-                || value.iter().filter(|ct| *ct == unique[1]).count() + jokers == 3
-                || value.iter().filter(|ct| *ct == unique[2]).count() + jokers == 3
-            {
-                return Self::ThreeOfKind;
-            } else {
-                return Self::TwoPair;
-            }
+impl From<&[usize]> for HandType {
+    /// Classify a hand from the sizes of its groups of matching cards, largest first. Hands with
+    /// more than five cards are classified by their largest groups.
+    fn from(shape: &[usize]) -> Self {
+        match shape {
+            [n, ..] if *n >= 5 => Self::FiveOfKind,
+            [4, ..] => Self::FourOfKind,
+            [3, n, ..] if *n >= 2 => Self::FullHouse,
+            [3, ..] => Self::ThreeOfKind,
+            [2, 2, ..] => Self::TwoPair,
+            [2, ..] => Self::OnePair,
+            _ => Self::HighCard,
         }
-        if unique.len() == 4 {
-            return Self::OnePair;
-        }
-        return Self::HighCard;
     }
 }
 
@@ -372,7 +250,79 @@ QQQJA 483"#;
 
     #[test]
     fn part2_orderings() {
-        assert!(Hand2::new("KTJJT 1") < Hand2::new("QQQJA 1"));
-        assert!(Hand2::new("22222 1") < Hand2::new("2222J 1"));
+        let rules = Rules::jokers();
+        assert!(rules.hand("KTJJT 1").unwrap() < rules.hand("QQQJA 1").unwrap());
+        assert!(rules.hand("22222 1").unwrap() < rules.hand("2222J 1").unwrap());
+    }
+
+    #[test]
+    fn test_hand_equality() {
+        let rules = Rules::standard();
+        // Equal rank means equal, whatever the bid
+        assert_eq!(rules.hand("32T3K 1").unwrap(), rules.hand("32T3K 765").unwrap());
+        assert_ne!(rules.hand("32T3K 1").unwrap(), rules.hand("32T3Q 1").unwrap());
+    }
+
+    #[test]
+    fn test_invalid_hands() {
+        let rules = Rules::standard();
+        assert!(rules.hand("32X3K 1").is_err());
+        assert!(rules.hand("32T3 1").is_err());
+        assert!(rules.hand("32T3K").is_err());
+        assert!(rules.hand("32T3K bid").is_err());
+        assert!(explain("32T3K\n", 1).is_err());
+        assert_eq!(Day07.solve1("32T3K\n"), None);
+    }
+
+    #[test]
+    fn test_hand_types() {
+        let standard = Rules::standard();
+        let jokers = Rules::jokers();
+        assert_eq!(standard.hand("32T3K 1").unwrap().hand_type, HandType::OnePair);
+        assert_eq!(standard.hand("KTJJT 1").unwrap().hand_type, HandType::TwoPair);
+        assert_eq!(jokers.hand("KTJJT 1").unwrap().hand_type, HandType::FourOfKind);
+        assert_eq!(jokers.hand("JJJJJ 1").unwrap().hand_type, HandType::FiveOfKind);
+        assert_eq!(jokers.hand("2233J 1").unwrap().hand_type, HandType::FullHouse);
+        assert_eq!(standard.hand("23456 1").unwrap().hand_type, HandType::HighCard);
+    }
+
+    #[test]
+    fn test_substitute() {
+        let jokers = Rules::jokers();
+        assert_eq!(jokers.hand("KTJJT 1").unwrap().substitute, Some('T'));
+        // Ties go to the stronger card
+        assert_eq!(jokers.hand("KTJJK 1").unwrap().substitute, Some('K'));
+        assert_eq!(jokers.hand("JJJJJ 1").unwrap().substitute, Some('A'));
+        assert_eq!(jokers.hand("KTQQT 1").unwrap().substitute, None);
+    }
+
+    #[test]
+    fn test_explain() {
+        let output = explain(EXAMPLE, 2).unwrap();
+        let lines = output.lines().collect_vec();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[1], "    1 |  32T3K  |   OnePair    |    -     |    765 |        765");
+        assert_eq!(lines[5], "    5 |  KTJJT  |  FourOfKind  |    T     |    220 |       1100");
+        assert_eq!(lines[6], "Total winnings: 5905");
+        assert!(explain(EXAMPLE, 3).is_err());
+    }
+
+    #[test]
+    fn test_multiple_wildcards() {
+        let rules = Rules::new("AKQT98765432J", "J2", 5);
+        assert_eq!(rules.hand("K2J3Q 1").unwrap().hand_type, HandType::ThreeOfKind);
+        assert_eq!(rules.hand("K2JKQ 1").unwrap().hand_type, HandType::FourOfKind);
+        // Wildcards still break ties by their own strength
+        assert!(rules.hand("K2KKK 1").unwrap() < rules.hand("KJKKK 1").unwrap());
+    }
+
+    #[test]
+    fn test_six_card_hands() {
+        let rules = Rules::new("AKQJT98765432", "", 6);
+        assert_eq!(rules.hand("AAAAAA 1").unwrap().hand_type, HandType::FiveOfKind);
+        assert!(rules.hand("222222 1").unwrap() < rules.hand("AAAAAK 1").unwrap());
+        assert!(rules.hand("222333 1").unwrap() < rules.hand("AAA234 1").unwrap());
+        assert!(rules.hand("223344 1").unwrap() < rules.hand("AA2345 1").unwrap());
+        assert_eq!(rules.winnings("222222 10\nAAAAAK 1\n").unwrap(), 21);
     }
 }
@@ -1,41 +1,26 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Write};
 
+use color_eyre::eyre::{bail, eyre, Result};
 use itertools::Itertools;
-use num::Integer;
 
-use crate::solver::Solver;
+use crate::{
+    solver::Solver,
+    utils::{
+        cycle::{self, Cycle},
+        math, parse,
+    },
+};
 
 pub struct Day08;
 impl Solver for Day08 {
     #[allow(unused)]
     fn solve1(&self, input: &str) -> Option<i64> {
-        let mut parts = input.split("\n\n");
-        let sequence = parts
-            .next()
-            .unwrap()
-            .trim()
-            .chars()
-            .map(|c| match c {
-                'L' => 0,
-                'R' => 1,
-                _ => unimplemented!("Got direction {}!", c),
-            })
-            .collect_vec();
-        let nodes = parts
-            .next()
-            .unwrap()
-            .split("\n")
-            .filter(|s| !s.is_empty())
-            .map(parse_node)
-            .collect::<HashMap<_, _>>();
-        let mut current = String::from("AAA");
+        let network = Network::new(input).ok()?;
+        let mut current = network.id("AAA")?;
+        let end = network.id("ZZZ")?;
         let mut steps: i64 = 0;
-        loop {
-            if current == "ZZZ" {
-                break;
-            }
-            current =
-                nodes.get(&current).unwrap()[sequence[steps as usize % sequence.len()]].to_owned();
+        while current != end {
+            current = network.next(current, steps as usize);
             steps += 1;
         }
         Some(steps)
@@ -43,49 +28,229 @@ impl Solver for Day08 {
 
     #[allow(unused)]
     fn solve2(&self, input: &str) -> Option<i64> {
-        let mut parts = input.split("\n\n");
-        let sequence = parts
-            .next()
-            .unwrap()
+        let network = Network::new(input).ok()?;
+        ghost_steps(&network).map(|solution| solution.steps)
+    }
+}
+
+/// The instructions and the left/right network of nodes, with each node given a dense ID.
+#[derive(Debug)]
+struct Network {
+    /// 0 for left, 1 for right.
+    instructions: Vec<usize>,
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    /// Left and right node for each node ID.
+    edges: Vec<[usize; 2]>,
+}
+
+impl Network {
+    fn new(input: &str) -> Result<Self> {
+        let [instructions, nodes] = parse::split_n(input.trim(), "\n\n")?;
+        let instructions = instructions
             .trim()
             .chars()
             .map(|c| match c {
-                'L' => 0,
-                'R' => 1,
-                _ => unimplemented!("Got direction {}!", c),
+                'L' => Ok(0),
+                'R' => Ok(1),
+                _ => Err(eyre!("Got direction {}!", c)),
             })
-            .collect_vec();
-        let nodes = parts
-            .next()
-            .unwrap()
-            .split("\n")
+            .collect::<Result<Vec<_>>>()?;
+        if instructions.is_empty() {
+            bail!("No instructions");
+        }
+        let lines = nodes
+            .lines()
             .filter(|s| !s.is_empty())
-            .map(parse_node)
-            .collect::<HashMap<_, _>>();
-        let mut current = nodes
-            .keys()
-            .filter(|s| s.chars().last().unwrap() == 'A')
-            .collect_vec();
-        let mut steps: i64 = 0;
-        current.iter()
-            .map(|current| {
-                let mut current = current.to_owned();
-                let mut steps: i64 = 0;
-                loop {
-                    if current.chars().last().unwrap() == 'Z' { break; }
-                    current = &nodes.get(current).unwrap()[sequence[steps as usize % sequence.len()]];
-                    steps += 1;
-                }
-                return steps;
-            })
-        .reduce(|a: i64, b: i64| a.lcm(&b))
+            .map(parse::key_pair)
+            .collect::<Result<Vec<_>>>()?;
+
+        let names = lines.iter().map(|(key, _, _)| key.to_string()).collect_vec();
+        let mut ids = HashMap::with_capacity(names.len());
+        for (id, name) in names.iter().enumerate() {
+            if ids.insert(name.clone(), id).is_some() {
+                bail!("Node {} is defined more than once", name);
+            }
+        }
+        let id = |name: &str| ids.get(name).copied().ok_or_else(|| eyre!("Node {} is never defined", name));
+        let edges = lines
+            .iter()
+            .map(|(_, left, right)| Ok([id(left)?, id(right)?]))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { instructions, names, ids, edges })
+    }
+
+    fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    /// Node reached from `node` by following the instruction for `step`.
+    fn next(&self, node: usize, step: usize) -> usize {
+        self.edges[node][self.instructions[step % self.instructions.len()]]
+    }
+
+    fn is_start(&self, node: usize) -> bool {
+        self.names[node].ends_with('A')
+    }
+
+    fn is_end(&self, node: usize) -> bool {
+        self.names[node].ends_with('Z')
     }
 }
 
-fn parse_node(input: &str) -> (String, [String; 2]) {
-    let key = input[0..3].to_owned();
-    let values = [input[7..10].to_owned(), input[12..15].to_owned()];
-    (key, values)
+/// How a ghost's path repeats. Following the instructions from any node eventually returns to the
+/// same node at the same point in the instructions, after which the path loops forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GhostCycle {
+    /// Step the ghost first enters its loop at.
+    start: i64,
+    /// Number of steps to go around the loop once.
+    length: i64,
+    /// Steps before entering the loop when the ghost is on an end node.
+    prefix_hits: Vec<i64>,
+    /// Steps during the first time around the loop when the ghost is on an end node.
+    cycle_hits: Vec<i64>,
+}
+
+impl GhostCycle {
+    fn new(start: usize, network: &Network) -> Self {
+        let cycle = walk(start, network);
+        let (loop_start, length) = (cycle.start as i64, cycle.length as i64);
+        let (prefix_hits, cycle_hits) = (0..loop_start + length)
+            .filter(|step| network.is_end(cycle.state_at(*step as usize).0))
+            .partition(|step| *step < loop_start);
+        Self { start: loop_start, length, prefix_hits, cycle_hits }
+    }
+
+    fn at_end(&self, step: i64) -> bool {
+        if step < self.start {
+            self.prefix_hits.contains(&step)
+        } else {
+            self.cycle_hits.contains(&(self.start + (step - self.start) % self.length))
+        }
+    }
+}
+
+/// Follow the instructions from `start` until the ghost is back on a node at the same point in the
+/// instructions. States are (node, instruction index).
+fn walk(start: usize, network: &Network) -> Cycle<(usize, usize)> {
+    cycle::find_cycle((start, 0), |&(node, i)| {
+        (network.next(node, i), (i + 1) % network.instructions.len())
+    })
+}
+
+/// Which approach found the number of steps for every ghost to be on an end node at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GhostMethod {
+    /// Every ghost reached an end node together before all of them were in their loops.
+    Prefix,
+    /// Each ghost reaches a single end node exactly once per loop, at a multiple of the loop
+    /// length, so the answer is the LCM of the loop lengths.
+    Lcm,
+    /// General case, combining every end node offset within each loop with the Chinese Remainder
+    /// Theorem.
+    Crt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GhostSolution {
+    steps: i64,
+    method: GhostMethod,
+}
+
+/// Number of steps until every ghost starting on a node ending in A is on a node ending in Z.
+fn ghost_steps(network: &Network) -> Option<GhostSolution> {
+    let ghosts = (0..network.names.len())
+        .filter(|node| network.is_start(*node))
+        .map(|start| GhostCycle::new(start, network))
+        .collect_vec();
+    let all_looping = ghosts.iter().map(|g| g.start).max()?;
+
+    // Before every ghost is in its loop, check each step directly.
+    if let Some(steps) = (0..all_looping).find(|step| ghosts.iter().all(|g| g.at_end(*step))) {
+        return Some(GhostSolution { steps, method: GhostMethod::Prefix });
+    }
+
+    if ghosts.iter().all(|g| g.cycle_hits == [g.length]) {
+        let steps = math::lcm_all(ghosts.iter().map(|g| g.length))?;
+        return Some(GhostSolution { steps, method: GhostMethod::Lcm });
+    }
+
+    // Try every combination of end node offsets, and take the earliest step after every ghost is
+    // in its loop.
+    ghosts
+        .iter()
+        .map(|g| g.cycle_hits.iter().map(move |hit| (*hit, g.length)))
+        .multi_cartesian_product()
+        .filter_map(math::crt)
+        .map(|(x, lcm)| {
+            if x < all_looping {
+                x + (all_looping - x + lcm - 1) / lcm * lcm
+            } else {
+                x
+            }
+        })
+        .min()
+        .map(|steps| GhostSolution { steps, method: GhostMethod::Crt })
+}
+
+/// Colours for the edges of each ghost's loop.
+const GHOST_COLOURS: [&str; 8] = [
+    "red", "blue", "darkgreen", "purple", "orange", "deeppink", "cyan4", "saddlebrown",
+];
+
+/// Graphviz DOT description of the network, with start nodes in green, end nodes in red, and the
+/// edges of each ghost's loop in its own colour.
+pub fn to_dot(input: &str) -> Result<String> {
+    let network = Network::new(input)?;
+    // Colours of the ghost loops each (node, direction) edge is part of
+    let mut edge_colours: HashMap<(usize, usize), Vec<&str>> = HashMap::new();
+    let starts = (0..network.names.len()).filter(|node| network.is_start(*node));
+    for (ghost, start) in starts.enumerate() {
+        let cycle = walk(start, &network);
+        let colour = GHOST_COLOURS[ghost % GHOST_COLOURS.len()];
+        for step in cycle.start..cycle.start + cycle.length {
+            let (node, i) = *cycle.state_at(step);
+            let colours = edge_colours.entry((node, network.instructions[i])).or_default();
+            if !colours.contains(&colour) {
+                colours.push(colour);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    writeln!(out, "digraph network {{")?;
+    match ghost_steps(&network) {
+        Some(solution) => writeln!(
+            out,
+            "    label=\"Ghosts all reach an end node after {} steps ({:?})\";",
+            solution.steps, solution.method
+        )?,
+        None => writeln!(out, "    label=\"Ghosts never all reach an end node together\";")?,
+    }
+    writeln!(out, "    node [shape=ellipse];")?;
+    for (node, name) in network.names.iter().enumerate() {
+        if network.is_start(node) {
+            writeln!(out, "    \"{}\" [style=filled, fillcolor=palegreen];", name)?;
+        } else if network.is_end(node) {
+            writeln!(out, "    \"{}\" [style=filled, fillcolor=lightcoral];", name)?;
+        }
+    }
+    for (node, edges) in network.edges.iter().enumerate() {
+        for (direction, label) in ["L", "R"].iter().enumerate() {
+            write!(
+                out,
+                "    \"{}\" -> \"{}\" [label={}",
+                network.names[node], network.names[edges[direction]], label
+            )?;
+            if let Some(colours) = edge_colours.get(&(node, direction)) {
+                write!(out, ", color=\"{}\", penwidth=2", colours.join(":"))?;
+            }
+            writeln!(out, "];")?;
+        }
+    }
+    writeln!(out, "}}")?;
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -124,4 +289,143 @@ XXX = (XXX, XXX)
         let output = Day08.solve2(input);
         assert_eq!(output, Some(6))
     }
+
+    #[test]
+    fn test_network() {
+        let network = Network::new("RL\n\nstart = (left, middle)\nleft = (left, left)\nmiddle = (start, middle)\n").unwrap();
+        assert_eq!(network.names, vec!["start", "left", "middle"]);
+        assert_eq!(network.edges, vec![[1, 2], [1, 1], [0, 2]]);
+        assert_eq!(network.id("middle"), Some(2));
+        assert_eq!(network.next(0, 0), 2);
+        assert_eq!(network.next(0, 1), 1);
+
+        assert!(Network::new("L\n\nAAA = (BBB, AAA)\n").is_err());
+        assert!(Network::new("L\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)\n").is_err());
+        assert!(Network::new("LX\n\nAAA = (AAA, AAA)\n").is_err());
+        assert!(Network::new("L\n\nAAA = AAA, AAA\n").is_err());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let input = "L\n\n11A = (11Z, 11Z)\n11Z = (11Z, 11A)\n22A = (22A, 22A)\n";
+        let output = to_dot(input).unwrap();
+        assert_eq!(
+            output,
+            r#"digraph network {
+    label="Ghosts never all reach an end node together";
+    node [shape=ellipse];
+    "11A" [style=filled, fillcolor=palegreen];
+    "11Z" [style=filled, fillcolor=lightcoral];
+    "22A" [style=filled, fillcolor=palegreen];
+    "11A" -> "11Z" [label=L];
+    "11A" -> "11Z" [label=R];
+    "11Z" -> "11Z" [label=L, color="red", penwidth=2];
+    "11Z" -> "11A" [label=R];
+    "22A" -> "22A" [label=L, color="blue", penwidth=2];
+    "22A" -> "22A" [label=R];
+}
+"#
+        );
+        assert!(to_dot("L\n\nAAA = (BBB, AAA)\n").is_err());
+    }
+
+    #[test]
+    fn test_ghost_cycle() {
+        let network = Network::new(
+            r#"LR
+
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)
+"#,
+        )
+        .unwrap();
+        let cycle = GhostCycle::new(network.id("22A").unwrap(), &network);
+        assert_eq!(
+            cycle,
+            GhostCycle { start: 1, length: 6, prefix_hits: vec![], cycle_hits: vec![3, 6] }
+        );
+        assert!(cycle.at_end(9));
+        assert!(!cycle.at_end(10));
+    }
+
+    #[test]
+    fn test_ghost_methods() {
+        // The part 2 example has a ghost reaching an end node twice per loop
+        let network = Network::new(
+            r#"LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)
+"#,
+        )
+        .unwrap();
+        assert_eq!(ghost_steps(&network), Some(GhostSolution { steps: 6, method: GhostMethod::Crt }));
+
+        let network = Network::new(
+            r#"L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11B, 11B)
+33A = (33B, 33B)
+33B = (33C, 33C)
+33C = (33Z, 33Z)
+33Z = (33B, 33B)
+"#,
+        )
+        .unwrap();
+        assert_eq!(ghost_steps(&network), Some(GhostSolution { steps: 6, method: GhostMethod::Lcm }));
+
+        // The LCM of the first end node steps (1 and 3) would be wrong here
+        let network = Network::new(
+            r#"L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11C, 11C)
+11C = (11Z, 11Z)
+22A = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22A, 22A)
+"#,
+        )
+        .unwrap();
+        assert_eq!(ghost_steps(&network), Some(GhostSolution { steps: 7, method: GhostMethod::Crt }));
+
+        let network = Network::new(
+            r#"L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22C, 22C)
+22C = (22C, 22C)
+"#,
+        )
+        .unwrap();
+        assert_eq!(ghost_steps(&network), Some(GhostSolution { steps: 1, method: GhostMethod::Prefix }));
+
+        // Ghosts never on an end node together
+        let network = Network::new(
+            r#"L
+
+11A = (11Z, 11Z)
+11Z = (11A, 11A)
+22A = (22A, 22A)
+"#,
+        )
+        .unwrap();
+        assert_eq!(ghost_steps(&network), None);
+    }
 }
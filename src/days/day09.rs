@@ -1,59 +1,131 @@
-use itertools::Itertools;
+use std::fmt::Write;
 
-use crate::solver::Solver;
+use color_eyre::eyre::{bail, eyre, Result};
+use num::{rational::Ratio, CheckedAdd, CheckedDiv, CheckedMul};
+
+use crate::{solver::Solver, utils::parse};
 
 pub struct Day09;
 impl Solver for Day09 {
     #[allow(unused)]
     fn solve1(&self, input: &str) -> Option<i64> {
-        Some(
-            input
-                .split("\n")
-                .filter(|s| !s.is_empty())
-                .map(|s| {
-                    s.split_whitespace()
-                        .map(|s| s.parse::<i64>().unwrap())
-                        .collect_vec()
-                })
-                .map(predict)
-                .sum(),
-        )
+        extrapolate_all(input, 1).ok()
     }
 
     #[allow(unused)]
     fn solve2(&self, input: &str) -> Option<i64> {
-        Some(
-            input
-                .split("\n")
-                .filter(|s| !s.is_empty())
-                .map(|s| {
-                    s.split_whitespace()
-                        .map(|s| s.parse::<i64>().unwrap())
-                        .rev()
-                        .collect_vec()
-                })
-                .map(predict)
-                .sum(),
-        )
+        extrapolate_all(input, -1).ok()
     }
 }
 
-fn predict(history: Vec<i64>) -> i64 {
-    let mut diffs: Vec<Vec<i64>> = vec![history];
-    loop {
-        if diffs.last().unwrap().iter().all(|&n| n == 0) {
-            break;
-        }
-        diffs.push(
-            diffs
-                .last()
-                .unwrap()
+/// Sum of every history in the input extrapolated by `steps`.
+fn extrapolate_all(input: &str, steps: i64) -> Result<i64> {
+    input
+        .lines()
+        .filter(|s| !s.is_empty())
+        .try_fold(0i64, |total, line| {
+            let value = Sequence::fit(&parse::ints(line)?)?.extrapolate(steps)?;
+            total
+                .checked_add(value)
+                .ok_or_else(|| eyre!("Sum of extrapolated values overflows"))
+        })
+}
+
+/// The polynomial through a history of values, stored as the first value of each row of the
+/// difference table (Newton's forward differences).
+#[derive(Debug)]
+struct Sequence {
+    differences: Vec<i64>,
+    len: usize,
+}
+
+impl Sequence {
+    /// Build the difference table for `history`, which must reach a row of zeros before it runs
+    /// out of values.
+    fn fit(history: &[i64]) -> Result<Self> {
+        let mut differences = vec![];
+        let mut row = history.to_vec();
+        while !row.iter().all(|&n| n == 0) {
+            if row.len() < 2 {
+                bail!("Differences of {:?} never reach a row of zeros", history);
+            }
+            differences.push(row[0]);
+            row = row
                 .windows(2)
-                .map(|w| w[1] - w[0])
-                .collect_vec(),
-        );
+                .map(|w| w[1].checked_sub(w[0]))
+                .collect::<Option<_>>()
+                .ok_or_else(|| eyre!("Differences of {:?} overflow", history))?;
+        }
+        if row.is_empty() {
+            bail!("Can't fit an empty history");
+        }
+        Ok(Self {
+            differences,
+            len: history.len(),
+        })
+    }
+
+    /// Degree of the polynomial, with a constant sequence having degree 0.
+    fn degree(&self) -> usize {
+        self.differences.len().saturating_sub(1)
+    }
+
+    /// The value at position `x`, where the history covers positions `0..len`.
+    fn value_at(&self, x: i64) -> Result<i64> {
+        // f(x) = sum of (x choose k) * (k-th difference), where (x choose k) is extended to any
+        // integer x as x (x - 1) ... (x - k + 1) / k!
+        let overflow = || eyre!("Value at {} overflows", x);
+        let position = Ratio::from_integer(x as i128);
+        let mut choose = Ratio::from_integer(1);
+        let mut value = Ratio::from_integer(0i128);
+        for (k, &difference) in self.differences.iter().enumerate() {
+            if k > 0 {
+                let k = Ratio::from_integer(k as i128);
+                choose = choose
+                    .checked_mul(&(position - k + 1))
+                    .and_then(|c| c.checked_div(&k))
+                    .ok_or_else(overflow)?;
+            }
+            value = choose
+                .checked_mul(&Ratio::from_integer(difference as i128))
+                .and_then(|term| value.checked_add(&term))
+                .ok_or_else(overflow)?;
+        }
+        if !value.is_integer() {
+            bail!("Value at {} isn't a whole number: {}", x, value);
+        }
+        i64::try_from(value.to_integer()).map_err(|_| eyre!("Value at {} overflows: {}", x, value))
+    }
+
+    /// The value `steps` past the end of the history, or before the start if `steps` is negative.
+    fn extrapolate(&self, steps: i64) -> Result<i64> {
+        if steps < 0 {
+            self.value_at(steps)
+        } else {
+            let x = (self.len as i64 - 1)
+                .checked_add(steps)
+                .ok_or_else(|| eyre!("{} steps past the end overflows", steps))?;
+            self.value_at(x)
+        }
     }
-    diffs.iter().rev().map(|d| d.last().unwrap()).sum()
+}
+
+/// The degree of each history's polynomial and its value `steps` past the end, or before the
+/// start if `steps` is negative.
+pub fn describe_extrapolation(input: &str, steps: i64) -> Result<String> {
+    let mut out = String::new();
+    for (n, line) in input.lines().filter(|s| !s.is_empty()).enumerate() {
+        let sequence = Sequence::fit(&parse::ints(line)?)?;
+        writeln!(
+            out,
+            "Line {}: degree {}, {} steps gives {}",
+            n + 1,
+            sequence.degree(),
+            steps,
+            sequence.extrapolate(steps)?
+        )?;
+    }
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -73,9 +145,61 @@ mod tests {
 
     #[test]
     fn test_predict() {
-        assert_eq!(predict(vec![0, 3, 6, 9, 12, 15]), 18);
-        assert_eq!(predict(vec![10, 13, 16, 21, 30, 45]), 68);
-        assert_eq!(predict(vec![15, 12, 9, 6, 3, 0]), -3);
+        let predict = |history: &[i64]| Sequence::fit(history).unwrap().extrapolate(1).unwrap();
+        assert_eq!(predict(&[0, 3, 6, 9, 12, 15]), 18);
+        assert_eq!(predict(&[10, 13, 16, 21, 30, 45]), 68);
+        assert_eq!(predict(&[15, 12, 9, 6, 3, 0]), -3);
+    }
+
+    #[test]
+    fn test_sequence() {
+        let sequence = Sequence::fit(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(sequence.degree(), 3);
+        assert_eq!(sequence.extrapolate(-1).unwrap(), 5);
+        assert_eq!(sequence.extrapolate(0).unwrap(), 45);
+        assert_eq!(sequence.value_at(3).unwrap(), 21);
+        assert_eq!(sequence.extrapolate(2).unwrap(), 101);
+        // n^2 + 1, fitted from n = 0..4
+        let squares = Sequence::fit(&[1, 2, 5, 10]).unwrap();
+        assert_eq!(squares.degree(), 2);
+        assert_eq!(squares.value_at(-5).unwrap(), 26);
+        assert_eq!(squares.extrapolate(97).unwrap(), 10_001);
+        assert_eq!(Sequence::fit(&[7, 7]).unwrap().degree(), 0);
+        assert_eq!(Sequence::fit(&[0]).unwrap().extrapolate(-3).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_describe_extrapolation() {
+        let output = describe_extrapolation(EXAMPLE, -1).unwrap();
+        assert_eq!(
+            output,
+            "Line 1: degree 1, -1 steps gives -3\nLine 2: degree 2, -1 steps gives 0\nLine 3: degree 3, -1 steps gives 5\n"
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        // Linear sequences stay small enough to extrapolate a long way
+        let linear = Sequence::fit(&[0, 3, 6, 9]).unwrap();
+        assert_eq!(
+            linear.extrapolate(100_000_000_000).unwrap(),
+            300_000_000_009
+        );
+        assert!(linear.extrapolate(i64::MAX).is_err());
+        let cubic = Sequence::fit(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert!(cubic.extrapolate(100_000_000_000).is_err());
+        assert!(cubic.extrapolate(-100_000_000_000).is_err());
+        assert!(Sequence::fit(&[i64::MIN, i64::MAX]).is_err());
+        let max = i64::MAX;
+        assert!(extrapolate_all(&format!("{max} {max}\n{max} {max}\n"), 1).is_err());
+    }
+
+    #[test]
+    fn test_no_zero_row() {
+        assert!(Sequence::fit(&[1, 2, 4, 8]).is_err());
+        assert!(Sequence::fit(&[5]).is_err());
+        assert!(Sequence::fit(&[]).is_err());
+        assert!(extrapolate_all("1 2 4 8\n", 1).is_err());
     }
 
     #[test]
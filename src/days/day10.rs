@@ -1,85 +1,42 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt::Write};
 
+use clap::ValueEnum;
+use color_eyre::eyre::{bail, eyre, Result};
 use itertools::Itertools;
 
-use crate::solver::Solver;
+use crate::{solver::Solver, utils::geometry};
 
 pub struct Day10;
 impl Solver for Day10 {
     #[allow(unused)]
     fn solve1(&self, input: &str) -> Option<i64> {
-        let mut map = Grid::new(input);
-        let mut steps = 0i64;
-        loop {
-            let stepping = map.step();
-            steps += 1;
-            if !stepping {
-                break;
-            }
-        }
-        Some(steps / 2)
+        let mut map = Grid::new(input).ok()?;
+        map.follow_loop().ok()?;
+        Some(map.in_order.len() as i64 / 2)
     }
 
     #[allow(unused)]
     fn solve2(&self, input: &str) -> Option<i64> {
-        let mut map = Grid::new(input);
-        loop {
-            if !map.step() {
-                break;
-            }
-        }
-
-        // Calculate turning direction of the loop in the order that we followed it.
-        let inside_direction = map.get_turn_direction();
-        // Holds all marked positions (either a pipe in the loop or an already marked interior) for
-        // fast membership tests.
-        let mut marked_positions_set: HashSet<Pos> =
-            HashSet::from_iter(map.in_order.iter().cloned());
-        // We will insert into this marked edges of the interior, then add the gaps.
-        let mut interior_positions: HashSet<Pos> = HashSet::new();
-
-        // Number of points on the pipe loop.
-        let plen = map.in_order.len();
-
-        // Find edges of interior regions
-        for i in 0..plen {
-            let marked = map.in_order[i]
-                .adjacent_points(
-                    map.in_order[wrap_dec(i, plen)],
-                    map.in_order[(i + 1) % plen],
-                    map.h,
-                    map.w,
-                    inside_direction,
-                )
-                .into_iter()
-                .filter(|p| !marked_positions_set.contains(p))
-                .collect_vec();
-            marked_positions_set.extend(marked.iter());
-            interior_positions.extend(marked.iter());
-        }
-
-        // Fill gaps in interior region by extending each interior edge in one direction until a
-        // previously marked position is reached. Because we should have found all edge positions
-        // that make up the interior positions already, choosing any direction will work equally
-        // well.
-        // The arbitrarily chosen extension direction is: South
-        for i in interior_positions.clone().iter() {
-            // Assuming we will hit a pipe or previously marked interior block before reaching the
-            // bottom of the map, so unwrap should be safe.
-            let mut current_pos = i.south(plen).unwrap();
-            loop {
-                if marked_positions_set.contains(&current_pos) {
-                    break;
-                }
-                interior_positions.insert(current_pos);
-                marked_positions_set.insert(current_pos);
-                current_pos = current_pos.south(plen).unwrap();
-            }
-        }
-        Some(interior_positions.len().try_into().unwrap())
+        let mut map = Grid::new(input).ok()?;
+        map.follow_loop().ok()?;
+        map.count_interior(Strategy::Shoelace).ok()
     }
 }
 
+/// Ways of counting the tiles enclosed by the loop. They should always agree; the shoelace
+/// formula is the fastest, so it's the one used for the answer.
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    /// Mark the tiles on the inside edge of the loop, found from the direction it turns, then fill
+    /// the gaps between them.
+    FloodFill,
+    /// Scan each row, flipping between outside and inside at every loop tile that connects north.
+    Scanline,
+    /// Shoelace formula for the area of the loop, then Pick's theorem for the tiles inside it.
+    Shoelace,
+}
+
 fn wrap_dec(i: usize, modulo: usize) -> usize {
     if i == 0 {
         modulo - 1
@@ -101,8 +58,8 @@ enum Pipe {
 }
 
 impl Pipe {
-    fn new(c: char) -> Self {
-        match c {
+    fn new(c: char) -> Result<Self> {
+        Ok(match c {
             'S' => Self::Start,
             '|' => Self::NS,
             '-' => Self::EW,
@@ -111,7 +68,38 @@ impl Pipe {
             'F' => Self::SE,
             '7' => Self::SW,
             '.' => Self::Ground,
-            _ => unimplemented!("Got char {}!", c),
+            _ => bail!("Unknown tile {:?}", c),
+        })
+    }
+}
+
+impl Pipe {
+    /// Every pipe that connects two tiles.
+    const ALL: [Pipe; 6] = [Pipe::NS, Pipe::EW, Pipe::NE, Pipe::NW, Pipe::SE, Pipe::SW];
+
+    /// Directions the pipe connects to.
+    fn connections(self) -> &'static [Direction] {
+        match self {
+            Pipe::NS => &[Direction::North, Direction::South],
+            Pipe::EW => &[Direction::East, Direction::West],
+            Pipe::NE => &[Direction::North, Direction::East],
+            Pipe::NW => &[Direction::North, Direction::West],
+            Pipe::SE => &[Direction::South, Direction::East],
+            Pipe::SW => &[Direction::South, Direction::West],
+            Pipe::Start | Pipe::Ground => &[],
+        }
+    }
+
+    fn box_char(self) -> char {
+        match self {
+            Pipe::NS => '│',
+            Pipe::EW => '─',
+            Pipe::NE => '└',
+            Pipe::NW => '┘',
+            Pipe::SE => '┌',
+            Pipe::SW => '┐',
+            Pipe::Start => 'S',
+            Pipe::Ground => ' ',
         }
     }
 }
@@ -134,7 +122,7 @@ impl Pos {
         }
     }
     fn south(&self, max: usize) -> Option<Pos> {
-        if self.y < max {
+        if self.y + 1 < max {
             Some(Pos {
                 x: self.x,
                 y: self.y + 1,
@@ -144,7 +132,7 @@ impl Pos {
         }
     }
     fn east(&self, max: usize) -> Option<Pos> {
-        if self.x < max {
+        if self.x + 1 < max {
             Some(Pos {
                 x: self.x + 1,
                 y: self.y,
@@ -164,6 +152,16 @@ impl Pos {
         }
     }
 
+    fn neighbour(&self, direction: Direction, h: usize, w: usize) -> Option<Pos> {
+        match direction {
+            Direction::North => self.north(),
+            Direction::South => self.south(h),
+            Direction::East => self.east(w),
+            Direction::West => self.west(),
+            Direction::NotAdjacent => None,
+        }
+    }
+
     fn compare(&self, other: Pos, h: usize, w: usize) -> Direction {
         if self.north() == Some(other) {
             Direction::North
@@ -281,6 +279,28 @@ enum Direction {
     NotAdjacent,
 }
 
+impl Direction {
+    fn opposite(self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+            Direction::NotAdjacent => Direction::NotAdjacent,
+        }
+    }
+
+    /// 1 for a right turn from this direction to `to`, -1 for a left turn and 0 otherwise.
+    fn turn(self, to: Direction) -> i32 {
+        use Direction::*;
+        match (self, to) {
+            (North, East) | (East, South) | (South, West) | (West, North) => 1,
+            (North, West) | (West, South) | (South, East) | (East, North) => -1,
+            _ => 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum TurnDirection {
     Left,
@@ -289,9 +309,12 @@ enum TurnDirection {
 
 #[derive(Debug)]
 struct Grid {
+    /// Pipes on each tile, with the start tile replaced by the pipe it must be.
     map: Vec<Vec<Pipe>>,
+    start: Pos,
     position: Pos,
-    last_position: Pos,
+    /// Direction of the last step taken.
+    heading: Direction,
     h: usize,
     w: usize,
     turn_direction: i32,
@@ -299,26 +322,146 @@ struct Grid {
 }
 
 impl Grid {
-    fn new(input: &str) -> Self {
-        let map = input
+    fn new(input: &str) -> Result<Self> {
+        let mut map = input
             .lines()
             .filter(|s| !s.is_empty())
-            .map(|s| s.chars().map(Pipe::new).collect_vec())
+            .map(|s| s.chars().map(Pipe::new).collect::<Result<Vec<_>>>())
+            .collect::<Result<Vec<_>>>()?;
+        let (h, w) = (map.len(), map.first().map_or(0, |row| row.len()));
+        if map.iter().any(|row| row.len() != w) {
+            bail!("Rows of the map are different widths");
+        }
+        let starts = (0..h)
+            .cartesian_product(0..w)
+            .filter(|&(y, x)| map[y][x] == Pipe::Start)
+            .map(|(y, x)| Pos { x, y })
             .collect_vec();
-        let y = map
-            .iter()
-            .position(|line| line.iter().any(|pipe| *pipe == Pipe::Start))
-            .unwrap();
-        let x = map[y].iter().position(|pipe| *pipe == Pipe::Start).unwrap();
-        let pos = Pos { x, y };
-        Self {
-            h: map.len(),
-            w: map[0].len(),
+        let &[start] = starts.as_slice() else {
+            bail!("Expected one start tile, found {}", starts.len());
+        };
+
+        // The start must be the pipe joining the two neighbours that connect back to it
+        let connected = [Direction::North, Direction::South, Direction::East, Direction::West]
+            .into_iter()
+            .filter(|&direction| {
+                start
+                    .neighbour(direction, h, w)
+                    .is_some_and(|p| map[p.y][p.x].connections().contains(&direction.opposite()))
+            })
+            .collect_vec();
+        let Some(pipe) = Pipe::ALL.into_iter().find(|pipe| {
+            connected.len() == 2 && connected.iter().all(|d| pipe.connections().contains(d))
+        }) else {
+            bail!(
+                "Start tile at {:?} has {} connecting pipes ({:?}), expected 2",
+                start,
+                connected.len(),
+                connected
+            );
+        };
+        map[start.y][start.x] = pipe;
+
+        Ok(Self {
+            h,
+            w,
             map,
-            position: pos,
-            last_position: pos,
+            start,
+            position: start,
+            heading: Direction::NotAdjacent,
             turn_direction: 0,
             in_order: vec![],
+        })
+    }
+
+    /// Step along the pipe until arriving back at the start.
+    fn follow_loop(&mut self) -> Result<()> {
+        while self.step()? {}
+        Ok(())
+    }
+
+    /// Every tile enclosed by the loop, which must have been followed already.
+    fn interior(&self) -> Result<HashSet<Pos>> {
+        // Calculate turning direction of the loop in the order that we followed it.
+        let inside_direction = self.get_turn_direction();
+        // Holds all marked positions (either a pipe in the loop or an already marked interior) for
+        // fast membership tests.
+        let mut marked_positions_set: HashSet<Pos> =
+            HashSet::from_iter(self.in_order.iter().cloned());
+        // We will insert into this marked edges of the interior, then add the gaps.
+        let mut interior_positions: HashSet<Pos> = HashSet::new();
+
+        // Number of points on the pipe loop.
+        let plen = self.in_order.len();
+
+        // Find edges of interior regions
+        for i in 0..plen {
+            let marked = self.in_order[i]
+                .adjacent_points(
+                    self.in_order[wrap_dec(i, plen)],
+                    self.in_order[(i + 1) % plen],
+                    self.h,
+                    self.w,
+                    inside_direction,
+                )
+                .into_iter()
+                .filter(|p| !marked_positions_set.contains(p))
+                .collect_vec();
+            marked_positions_set.extend(marked.iter());
+            interior_positions.extend(marked.iter());
+        }
+
+        // Fill gaps in interior region by extending each interior edge in one direction until a
+        // previously marked position is reached. Because we should have found all edge positions
+        // that make up the interior positions already, choosing any direction will work equally
+        // well.
+        // The arbitrarily chosen extension direction is: South
+        for i in interior_positions.clone().iter() {
+            // An interior tile always has the loop somewhere below it, so running off the bottom
+            // of the map means the interior edges were wrong.
+            let mut current_pos = *i;
+            loop {
+                current_pos = current_pos
+                    .south(self.h)
+                    .ok_or_else(|| eyre!("Filling the interior from {:?} left the map", i))?;
+                if marked_positions_set.contains(&current_pos) {
+                    break;
+                }
+                interior_positions.insert(current_pos);
+                marked_positions_set.insert(current_pos);
+            }
+        }
+        Ok(interior_positions)
+    }
+
+    /// Number of tiles enclosed by the loop, which must have been followed already.
+    fn count_interior(&self, strategy: Strategy) -> Result<i64> {
+        match strategy {
+            Strategy::FloodFill => Ok(self.interior()?.len() as i64),
+            Strategy::Scanline => {
+                let mut on_loop = vec![vec![false; self.w]; self.h];
+                for p in &self.in_order {
+                    on_loop[p.y][p.x] = true;
+                }
+                let mut count = 0;
+                for (y, row) in on_loop.iter().enumerate() {
+                    // A ray along the row crosses the loop at every pipe with a north end, and
+                    // only runs alongside it at the rest.
+                    let mut inside = false;
+                    for (x, &looped) in row.iter().enumerate() {
+                        if !looped {
+                            count += inside as i64;
+                        } else if self.map[y][x].connections().contains(&Direction::North) {
+                            inside = !inside;
+                        }
+                    }
+                }
+                Ok(count)
+            }
+            Strategy::Shoelace => {
+                let vertices = self.in_order.iter().map(|p| (p.x as i64, p.y as i64)).collect_vec();
+                Ok(geometry::interior_points(&vertices))
+            }
         }
     }
 
@@ -334,121 +477,162 @@ impl Grid {
         }
     }
 
-    /// Steps along the pipe. Returns false if the start point has been reached, true otherwise.
-    fn step(&mut self) -> bool {
+    /// Steps along the pipe. Returns false if the start point has been reached, true otherwise,
+    /// or an error if the pipe doesn't lead back to the start.
+    fn step(&mut self) -> Result<bool> {
         self.in_order.push(self.position);
-        let current = self.position;
-        let north = self.position.north();
-        let south = self.position.south(self.h);
-        let east = self.position.east(self.w);
-        let west = self.position.west();
-        // Should only be true if we are at the start
-        if self.position == self.last_position {
-            if north.is_some()
-                && match self.pipe_at(self.position.north().unwrap()) {
-                    Pipe::NS | Pipe::SE | Pipe::SW => true,
-                    _ => false,
-                }
-            {
-                self.position = north.unwrap();
-            } else if south.is_some()
-                && match self.pipe_at(south.unwrap()) {
-                    Pipe::NS | Pipe::NE | Pipe::NW => true,
-                    _ => false,
-                }
-            {
-                self.position = south.unwrap();
-            } else if east.is_some()
-                && match self.pipe_at(east.unwrap()) {
-                    Pipe::EW | Pipe::NE | Pipe::SE => true,
-                    _ => false,
-                }
-            {
-                self.position = east.unwrap();
-            } else if west.is_some()
-                && match self.pipe_at(west.unwrap()) {
-                    Pipe::EW | Pipe::NW | Pipe::SW => true,
-                    _ => false,
-                }
-            {
-                self.position = west.unwrap();
-            }
+        let pipe = self.pipe_at(self.position);
+        let out = if self.position == self.start {
+            // Leaving the start, either way around the loop will do
+            pipe.connections()[0]
         } else {
-            match self.last_position.compare(self.position, self.h, self.w) {
-                Direction::North => match self.pipe_at(self.position) {
-                    Pipe::NS => self.position = north.unwrap(),
-                    Pipe::SE => {
-                        self.position = east.unwrap();
-                        self.turn_direction += 1;
-                    }
-                    Pipe::SW => {
-                        self.position = west.unwrap();
-                        self.turn_direction -= 1;
-                    }
-                    _ => panic!(
-                        "Going north, hit {:?} at {:?}",
-                        self.pipe_at(self.position),
-                        self.position
-                    ),
-                },
-                Direction::South => match self.pipe_at(self.position) {
-                    Pipe::NS => self.position = south.unwrap(),
-                    Pipe::NE => {
-                        self.position = east.unwrap();
-                        self.turn_direction -= 1;
-                    }
-                    Pipe::NW => {
-                        self.position = west.unwrap();
-                        self.turn_direction += 1;
-                    }
-                    _ => panic!(
-                        "Going south, hit {:?} at {:?}",
-                        self.pipe_at(self.position),
-                        self.position
-                    ),
-                },
-                Direction::East => match self.pipe_at(self.position) {
-                    Pipe::EW => self.position = east.unwrap(),
-                    Pipe::NW => {
-                        self.position = north.unwrap();
-                        self.turn_direction -= 1;
-                    }
-                    Pipe::SW => {
-                        self.position = south.unwrap();
-                        self.turn_direction += 1;
-                    }
-                    _ => panic!(
-                        "Going east, hit {:?} at {:?}",
-                        self.pipe_at(self.position),
-                        self.position
-                    ),
-                },
-                Direction::West => match self.pipe_at(self.position) {
-                    Pipe::EW => self.position = west.unwrap(),
-                    Pipe::NE => {
-                        self.position = north.unwrap();
-                        self.turn_direction += 1;
-                    }
-                    Pipe::SE => {
-                        self.position = south.unwrap();
-                        self.turn_direction -= 1;
+            // Leave by whichever end of the pipe we didn't come in by
+            let back = self.heading.opposite();
+            let out = match *pipe.connections() {
+                [a, b] if a == back => b,
+                [a, b] if b == back => a,
+                _ => bail!("{:?} at {:?} doesn't connect back to the {:?}", pipe, self.position, back),
+            };
+            self.turn_direction += self.heading.turn(out);
+            out
+        };
+        self.position = self
+            .position
+            .neighbour(out, self.h, self.w)
+            .ok_or_else(|| eyre!("{:?} at {:?} leads off the map", pipe, self.position))?;
+        self.heading = out;
+        if self.position != self.start {
+            return Ok(true);
+        }
+        if !self.pipe_at(self.start).connections().contains(&out.opposite()) {
+            bail!("Loop comes back into the start from the wrong side");
+        }
+        Ok(false)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RenderFormat {
+    /// Box drawing characters coloured with ANSI escape codes, for a terminal
+    Ansi,
+    /// Box drawing characters only, for a text file
+    Text,
+    /// An SVG image
+    Svg,
+}
+
+/// What a tile turned out to be once the loop has been found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Loop(Pipe),
+    Start(Pipe),
+    /// A pipe that isn't part of the loop
+    Junk(Pipe),
+    Inside,
+    Outside,
+}
+
+/// Draw the map with the loop in box drawing characters, junk pipes dimmed and the tiles inside
+/// the loop marked.
+pub fn render(input: &str, format: RenderFormat) -> Result<String> {
+    let mut map = Grid::new(input)?;
+    map.follow_loop()?;
+    let on_loop: HashSet<Pos> = map.in_order.iter().copied().collect();
+    let inside = map.interior()?;
+    let tiles = (0..map.h)
+        .map(|y| {
+            (0..map.w)
+                .map(|x| {
+                    let pos = Pos { x, y };
+                    match map.pipe_at(pos) {
+                        pipe if pos == map.start => Tile::Start(pipe),
+                        pipe if on_loop.contains(&pos) => Tile::Loop(pipe),
+                        _ if inside.contains(&pos) => Tile::Inside,
+                        Pipe::Ground => Tile::Outside,
+                        pipe => Tile::Junk(pipe),
                     }
-                    _ => panic!(
-                        "Going west, hit {:?} at {:?}",
-                        self.pipe_at(self.position),
-                        self.position
-                    ),
-                },
-                Direction::NotAdjacent => panic!("Last and current position are not adjacent!!!"),
+                })
+                .collect_vec()
+        })
+        .collect_vec();
+    match format {
+        RenderFormat::Ansi => render_text(&tiles, true),
+        RenderFormat::Text => render_text(&tiles, false),
+        RenderFormat::Svg => render_svg(&tiles, &map.in_order),
+    }
+}
+
+fn render_text(tiles: &[Vec<Tile>], ansi: bool) -> Result<String> {
+    const RESET: &str = "\x1b[0m";
+    let mut out = String::new();
+    for row in tiles {
+        for tile in row {
+            let (c, colour) = match *tile {
+                Tile::Loop(pipe) => (pipe.box_char(), "\x1b[1m"),
+                Tile::Start(pipe) => (pipe.box_char(), "\x1b[1;31m"),
+                Tile::Junk(pipe) if ansi => (pipe.box_char(), "\x1b[2m"),
+                Tile::Junk(_) => ('·', ""),
+                Tile::Inside => ('█', "\x1b[32m"),
+                Tile::Outside => (' ', ""),
+            };
+            if ansi && !colour.is_empty() {
+                write!(out, "{}{}{}", colour, c, RESET)?;
+            } else {
+                out.push(c);
             }
         }
-        self.last_position = current;
-        if self.pipe_at(self.position) == Pipe::Start {
-            false
-        } else {
-            true
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn render_svg(tiles: &[Vec<Tile>], in_order: &[Pos]) -> Result<String> {
+    const SIZE: usize = 10;
+    let centre = |n: usize| n * SIZE + SIZE / 2;
+    let (w, h) = (tiles.first().map_or(0, |row| row.len()) * SIZE, tiles.len() * SIZE);
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#
+    )?;
+    writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+    let mut junk = String::new();
+    for (y, row) in tiles.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            match tile {
+                Tile::Inside => writeln!(
+                    out,
+                    r#"<rect x="{}" y="{}" width="{SIZE}" height="{SIZE}" fill="palegreen"/>"#,
+                    x * SIZE,
+                    y * SIZE
+                )?,
+                Tile::Junk(pipe) => {
+                    // Half a pipe from the centre of the tile towards each edge it connects to
+                    for direction in pipe.connections() {
+                        let (dx, dy) = match direction {
+                            Direction::North => (0, -1),
+                            Direction::South => (0, 1),
+                            Direction::East => (1, 0),
+                            _ => (-1, 0),
+                        };
+                        let half = SIZE as i32 / 2;
+                        write!(junk, "M{} {}l{} {}", centre(x), centre(y), dx * half, dy * half)?;
+                    }
+                }
+                _ => {}
+            }
         }
     }
+    if !junk.is_empty() {
+        writeln!(out, r#"<path d="{}" stroke="lightgray" fill="none"/>"#, junk)?;
+    }
+    let points = in_order.iter().map(|p| format!("{},{}", centre(p.x), centre(p.y))).join(" ");
+    writeln!(out, r#"<polygon points="{}" stroke="black" stroke-width="2" fill="none"/>"#, points)?;
+    if let Some(start) = in_order.first() {
+        writeln!(out, r#"<circle cx="{}" cy="{}" r="3" fill="red"/>"#, centre(start.x), centre(start.y))?;
+    }
+    writeln!(out, "</svg>")?;
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -468,6 +652,75 @@ LJ...
         assert_eq!(output, Some(8));
     }
 
+    #[test]
+    fn test_render() {
+        let input = "-L|F7\n7S-7|\nL|7||\n-L-J|\nL|-JF\n";
+        assert_eq!(render(input, RenderFormat::Text).unwrap(), "·····\n·┌─┐·\n·│█│·\n·└─┘·\n·····\n");
+        let ansi = render(input, RenderFormat::Ansi).unwrap();
+        assert!(ansi.starts_with("\x1b[2m─\x1b[0m\x1b[2m└\x1b[0m"));
+        assert!(ansi.contains("\x1b[1;31m┌\x1b[0m"));
+        assert!(ansi.contains("\x1b[32m█\x1b[0m"));
+        let svg = render(input, RenderFormat::Svg).unwrap();
+        assert!(svg.contains(r#"<rect x="20" y="20" width="10" height="10" fill="palegreen"/>"#));
+        assert!(svg.contains(r#"<polygon points="15,15 15,25 15,35 25,35 35,35 35,25 35,15 25,15" "#));
+    }
+
+    #[test]
+    fn test_start_pipe() {
+        let map = Grid::new(EXAMPLE).unwrap();
+        assert_eq!(map.start, Pos { x: 0, y: 2 });
+        assert_eq!(map.pipe_at(map.start), Pipe::SE);
+        // Junk pipes next to the start that don't point at it are ignored
+        let map = Grid::new("-L|F7\n7S-7|\nL|7||\n-L-J|\nL|-JF\n").unwrap();
+        assert_eq!(map.pipe_at(map.start), Pipe::SE);
+    }
+
+    #[test]
+    fn test_invalid_loops() {
+        // No connections, too many connections, no start and two starts
+        assert!(Grid::new(".....\n..S..\n.....\n").is_err());
+        assert!(Grid::new(".|.\n-S-\n.|.\n").is_err());
+        assert!(Grid::new("F-7\n|.|\nL-J\n").is_err());
+        assert!(Grid::new("S-7\n|.|\nL-S\n").is_err());
+        // Loop that doesn't close
+        let mut map = Grid::new("S-7\n|.|\nL-.\n").unwrap();
+        assert!(map.follow_loop().is_err());
+        assert!(Grid::new("S-7\n|.|\nL-x\n").is_err());
+        assert!(Grid::new("S-7\n|.|\nL-\n").is_err());
+    }
+
+    #[test]
+    fn test_loop_on_border() {
+        let input = "S--7\n|..|\n|..|\nL--J\n";
+        assert_eq!(Day10.solve1(input), Some(6));
+        assert_eq!(Day10.solve2(input), Some(4));
+        assert_eq!(Day10.solve1("S7\nLJ\n"), Some(2));
+        assert_eq!(Day10.solve2("S7\nLJ\n"), Some(0));
+        let clockwise = "F--S\n|..|\nL--J\n";
+        assert_eq!(Day10.solve2(clockwise), Some(2));
+    }
+
+    #[test]
+    fn test_strategies_agree() {
+        let inputs = [
+            EXAMPLE,
+            "-L|F7\n7S-7|\nL|7||\n-L-J|\nL|-JF\n",
+            "S--7\n|..|\n|..|\nL--J\n",
+            "S7\nLJ\n",
+            "F--S\n|..|\nL--J\n",
+            ".F----7F7F7F7F-7....\n.|F--7||||||||FJ....\n.||.FJ||||||||L7....\nFJL7L7LJLJ||LJ.L-7..\n\
+             L--J.L7...LJS7F-7L7.\n....F-J..F7FJ|L7L7L7\n....L7.F7||L7|.L7L7|\n.....|FJLJ|FJ|F7|.LJ\n\
+             ....FJL-7.||.||||...\n....L---J.LJ.LJLJ...\n",
+        ];
+        for input in inputs {
+            let mut map = Grid::new(input).unwrap();
+            map.follow_loop().unwrap();
+            let flood_fill = map.count_interior(Strategy::FloodFill).unwrap();
+            assert_eq!(map.count_interior(Strategy::Scanline).unwrap(), flood_fill, "{}", input);
+            assert_eq!(map.count_interior(Strategy::Shoelace).unwrap(), flood_fill, "{}", input);
+        }
+    }
+
     #[test]
     fn part2() {
         let example1 = r#"...........
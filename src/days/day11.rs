@@ -1,4 +1,6 @@
-use std::{collections::HashSet, fmt::Display};
+use std::fmt::{Display, Write};
+
+use color_eyre::eyre::{bail, eyre, Result};
 
 use itertools::Itertools;
 
@@ -9,13 +11,13 @@ impl Solver for Day11 {
     #[allow(unused)]
     fn solve1(&self, input: &str) -> Option<i64> {
         let space = Space::new(input);
-        Some(space.distances(1).into_iter().sum())
+        space.total_distance(1)
     }
 
     #[allow(unused)]
     fn solve2(&self, input: &str) -> Option<i64> {
         let space = Space::new(input);
-        Some(space.distances(999_999).into_iter().sum())
+        space.total_distance(999_999)
     }
 }
 
@@ -37,10 +39,14 @@ impl From<char> for Point {
 
 struct Space {
     map: Vec<Vec<Point>>,
+    /// Galaxy coordinates in (x,y) format, in reading order.
+    galaxies: Vec<(usize, usize)>,
+    /// Number of empty columns to the left of each column.
+    empty_columns: Vec<usize>,
+    /// Number of empty rows above each row.
+    empty_rows: Vec<usize>,
 }
 
-
-/// Added for debugging purposes
 impl Display for Space {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for row in &self.map {
@@ -52,91 +58,159 @@ impl Display for Space {
             }
             f.write_str("\n")?;
         }
-        f.write_str("\n")?;
-        write!(f, "\n")
+        Ok(())
     }
 }
 
 impl Space {
     fn new(input: &str) -> Self {
-        Self {
-            map: input
+        Self::from_map(
+            input
                 .split("\n")
                 .filter(|s| !s.is_empty())
                 .map(|s| s.chars().map(Point::from).collect_vec())
                 .collect_vec(),
-        }
+        )
     }
 
-    /// Return list of galaxy coordinates in (x,y) format
-    fn list_galaxies(&self) -> Vec<(usize, usize)> {
-        self.map
+    fn from_map(map: Vec<Vec<Point>>) -> Self {
+        let galaxies = list_galaxies(&map);
+        let width = map.first().map_or(0, |row| row.len());
+        let empty_columns = empty_before(width, galaxies.iter().map(|g| g.0));
+        let empty_rows = empty_before(map.len(), galaxies.iter().map(|g| g.1));
+        Self { map, galaxies, empty_columns, empty_rows }
+    }
+
+    /// A copy of the map with every empty row and column repeated an extra `expansion_factor`
+    /// times. Only practical for small factors; use [`Space::expand`] for the coordinates alone.
+    fn expanded(&self, expansion_factor: usize) -> Space {
+        let copies = |empty: bool| if empty { 1 + expansion_factor } else { 1 };
+        let column_copies = (0..self.empty_columns.len())
+            .map(|x| copies(self.map.iter().all(|row| row[x] == Point::Space)))
+            .collect_vec();
+        let map = self
+            .map
             .iter()
-            .enumerate()
-            .flat_map(|(y, row)| {
-                row.iter()
-                    .enumerate()
-                    .filter(|(_, point)| **point == Point::Galaxy)
-                    .map(move |(x, _)| (x, y))
+            .flat_map(|row| {
+                let empty = row.iter().all(|p| *p == Point::Space);
+                let row = row
+                    .iter()
+                    .zip(&column_copies)
+                    .flat_map(|(point, n)| std::iter::repeat_n(*point, *n))
+                    .collect_vec();
+                std::iter::repeat_n(row, copies(empty))
             })
-            .collect_vec()
-    }
-
-    // Removed due to part 2 complications
-    //fn expand(&mut self, factor: usize) {
-    //    let mut rows = (0..self.map.len()).collect::<HashSet<_>>();
-    //    let mut columns = (0..self.map[0].len()).collect::<HashSet<_>>();
-    //    let galaxy_positions = self.list_galaxies();
-    //    for (x, y) in galaxy_positions.iter() {
-    //        rows.remove(&y);
-    //        columns.remove(&x);
-    //    }
-    //    // expand rows
-    //    let mut expansions = 0;
-    //    for row in rows.iter().sorted() {
-    //        for _ in 0..factor {
-    //            self.map
-    //                .insert(row + expansions, vec![Point::Space; self.map[0].len()]);
-    //            expansions += 1;
-    //        }
-    //    }
-    //    // expand columns
-    //    for row in 0..self.map.len() {
-    //        let mut expansions = 0;
-    //        for column in columns.iter().sorted() {
-    //            for _ in 0..factor {
-    //                self.map[row].insert(column + expansions, Point::Space);
-    //                expansions += 1;
-    //            }
-    //        }
-    //    }
-    //}
-
-    /// Find distances between every pair of galaxies.
+            .collect_vec();
+        Space::from_map(map)
+    }
+
+    /// Where a position in the original map ends up once every empty row and column is repeated an
+    /// extra `expansion_factor` times, or None if it's too far out for a u64.
+    fn expand(&self, (x, y): (usize, usize), expansion_factor: u64) -> Option<(u64, u64)> {
+        let expand = |i: usize, empty_before: usize| {
+            expansion_factor.checked_mul(empty_before as u64)?.checked_add(i as u64)
+        };
+        Some((expand(x, self.empty_columns[x])?, expand(y, self.empty_rows[y])?))
+    }
+
+    #[allow(unused)]
+    /// Distance between galaxies `a` and `b` (indexes into the galaxies in reading order), or None
+    /// if it doesn't fit in an i64.
     /// expansion_factor determines how much additional empty space is added in each row and column
     /// without galaxies. When set to 0 it will not add any additional space, when set to 999,999
     /// it make the empty space 1,000,000 times larger (as in part 2).
-    fn distances(&self, expansion_factor: usize) -> Vec<i64> {
-        let mut rows = (0..self.map.len()).collect::<HashSet<_>>();
-        let mut columns = (0..self.map[0].len()).collect::<HashSet<_>>();
-        let galaxy_positions = self.list_galaxies();
-        for (x, y) in galaxy_positions.iter() {
-            rows.remove(&y);
-            columns.remove(&x);
-        }
-        let mut distances = vec![];
-        for i in 0..galaxy_positions.len() {
-            for j in i..galaxy_positions.len() {
-                let a = galaxy_positions[i];
-                let b = galaxy_positions[j];
-                let base_distance = a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
-                let additional_x = expansion_factor * columns.iter().filter(|&c| *c < a.0.max(b.0) && *c > a.0.min(b.0)).count();
-                let additional_y = expansion_factor * rows.iter().filter(|&c| *c < a.1.max(b.1) && *c > a.1.min(b.1)).count();
-                distances.push((base_distance + additional_y + additional_x).try_into().unwrap());
-            }
+    fn distance(&self, a: usize, b: usize, expansion_factor: usize) -> Option<i64> {
+        let ((ax, ay), (bx, by)) = (self.galaxies[a], self.galaxies[b]);
+        let expanded = |from: usize, to: usize, empty_before: &[usize]| {
+            let empty = empty_before[from].abs_diff(empty_before[to]);
+            expansion_factor.checked_mul(empty)?.checked_add(from.abs_diff(to))
+        };
+        let total = expanded(ax, bx, &self.empty_columns)?.checked_add(expanded(ay, by, &self.empty_rows)?)?;
+        total.try_into().ok()
+    }
+
+    /// Sum of the distances between every pair of galaxies, with the same expansion_factor as
+    /// [`Space::distance`]. Returns None if it doesn't fit in an i64.
+    fn total_distance(&self, expansion_factor: usize) -> Option<i64> {
+        let expanded = self
+            .galaxies
+            .iter()
+            .map(|g| self.expand(*g, expansion_factor as u64))
+            .collect::<Option<Vec<_>>>()?;
+        let xs = expanded.iter().map(|g| g.0 as i128);
+        let ys = expanded.iter().map(|g| g.1 as i128);
+        (pairwise_distance_sum(xs) + pairwise_distance_sum(ys)).try_into().ok()
+    }
+}
+
+/// Return list of galaxy coordinates in (x,y) format
+fn list_galaxies(map: &[Vec<Point>]) -> Vec<(usize, usize)> {
+    map.iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, point)| **point == Point::Galaxy)
+                .map(move |(x, _)| (x, y))
+        })
+        .collect_vec()
+}
+
+/// For each of `len` rows or columns, the number before it with none of the given galaxy
+/// coordinates.
+fn empty_before(len: usize, occupied: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut has_galaxy = vec![false; len];
+    for i in occupied {
+        has_galaxy[i] = true;
+    }
+    has_galaxy
+        .iter()
+        .scan(0, |empty, &galaxy| {
+            let before = *empty;
+            *empty += !galaxy as usize;
+            Some(before)
+        })
+        .collect()
+}
+
+/// Sum of |a - b| over every pair of values. Once sorted, the i-th value is larger than the i
+/// before it and smaller than the n - 1 - i after it.
+fn pairwise_distance_sum(values: impl Iterator<Item = i128>) -> i128 {
+    let sorted = values.sorted_unstable().collect_vec();
+    let n = sorted.len() as i128;
+    sorted
+        .iter()
+        .enumerate()
+        .map(|(i, v)| v * (2 * i as i128 - n + 1))
+        .sum()
+}
+
+/// Largest expanded map that will be drawn, in tiles.
+const MAX_RENDER_SIZE: u64 = 1_000_000;
+
+/// The position of every galaxy before and after expansion, and optionally the expanded map when
+/// it's small enough to draw.
+pub fn describe_expansion(input: &str, expansion_factor: u64, render: bool) -> Result<String> {
+    let space = Space::new(input);
+    let overflow = || eyre!("Expanding by {} overflows a u64", expansion_factor);
+    let mut out = String::new();
+    for (i, galaxy) in space.galaxies.iter().enumerate() {
+        let (x, y) = space.expand(*galaxy, expansion_factor).ok_or_else(overflow)?;
+        writeln!(out, "Galaxy {}: ({}, {}) -> ({}, {})", i + 1, galaxy.0, galaxy.1, x, y)?;
+    }
+    if render {
+        let (height, width) = (space.empty_rows.len(), space.empty_columns.len());
+        let (w, h) = match (width.checked_sub(1), height.checked_sub(1)) {
+            (Some(x), Some(y)) => space.expand((x, y), expansion_factor).ok_or_else(overflow)?,
+            _ => (0, 0),
+        };
+        let size = (w as u128 + 1) * (h as u128 + 1);
+        if size > MAX_RENDER_SIZE as u128 {
+            bail!("Expanded map would be {} tiles, more than the limit of {}", size, MAX_RENDER_SIZE);
         }
-        distances
+        write!(out, "\n{}", space.expanded(expansion_factor as usize))?;
     }
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -160,36 +234,79 @@ mod tests {
         assert_eq!(output, Some(374));
     }
 
-// Removed due to part 2 complications
-//    #[test]
-//    fn test_expand() {
-//        let expanded = r#"....#........
-//.........#...
-//#............
-//.............
-//.............
-//........#....
-//.#...........
-//............#
-//.............
-//.............
-//.........#...
-//#....#......."#;
-//        let mut space = Space::new(EXAMPLE);
-//        space.expand(1);
-//        assert_eq!(
-//            space.map,
-//            expanded
-//                .split("\n")
-//                .map(|s| s.chars().map(Point::from).collect_vec())
-//                .collect_vec()
-//        )
-//    }
+    #[test]
+    fn test_expand() {
+        let expanded = r#"....#........
+.........#...
+#............
+.............
+.............
+........#....
+.#...........
+............#
+.............
+.............
+.........#...
+#....#......."#;
+        let space = Space::new(EXAMPLE);
+        assert_eq!(
+            space.expanded(1).map,
+            expanded
+                .split("\n")
+                .map(|s| s.chars().map(Point::from).collect_vec())
+                .collect_vec()
+        );
+        assert_eq!(space.expanded(1).to_string(), format!("{}\n", expanded));
+        assert_eq!(space.expanded(0).map, space.map);
+    }
+
+    #[test]
+    fn test_expand_coordinates() {
+        let space = Space::new(EXAMPLE);
+        let expanded = space.expanded(1);
+        let galaxies = space.galaxies.iter().map(|g| space.expand(*g, 1).unwrap()).collect_vec();
+        let literal = expanded.galaxies.iter().map(|&(x, y)| (x as u64, y as u64)).collect_vec();
+        assert_eq!(galaxies, literal);
+        assert_eq!(space.expand((9, 9), 999_999), Some((3_000_006, 2_000_007)));
+        assert_eq!(space.expand((9, 9), 1 << 60), Some((9 + 3 * (1 << 60), 9 + 2 * (1 << 60))));
+        assert_eq!(space.expand((9, 9), u64::MAX), None);
+        assert_eq!(space.expand((0, 0), u64::MAX), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_describe_expansion() {
+        let output = describe_expansion(EXAMPLE, 1, true).unwrap();
+        assert!(output.starts_with("Galaxy 1: (3, 0) -> (4, 0)\nGalaxy 2: (7, 1) -> (9, 1)\n"));
+        assert!(output.ends_with("\n\n....#........\n.........#...\n#............\n.............\n.............\n........#....\n.#...........\n............#\n.............\n.............\n.........#...\n#....#.......\n"));
+        assert!(!describe_expansion(EXAMPLE, 999_999, false).unwrap().contains("#"));
+        assert!(describe_expansion(EXAMPLE, 999_999, true).is_err());
+        assert!(describe_expansion(EXAMPLE, u64::MAX, false).is_err());
+    }
 
     #[test]
     fn test_large_expand() {
         let space = Space::new(EXAMPLE);
-        assert_eq!(space.distances(99).iter().sum::<i64>(), 8410);
+        assert_eq!(space.total_distance(99), Some(8410));
+        assert_eq!(space.total_distance(9), Some(1030));
+        assert_eq!(space.total_distance(1 << 60), None);
+        assert_eq!(space.total_distance(usize::MAX), None);
+    }
+
+    #[test]
+    fn test_distance() {
+        let space = Space::new(EXAMPLE);
+        assert_eq!(space.distance(4, 8, 1), Some(9));
+        assert_eq!(space.distance(0, 6, 1), Some(15));
+        assert_eq!(space.distance(2, 5, 1), Some(17));
+        assert_eq!(space.distance(7, 8, 1), Some(5));
+        assert_eq!(space.distance(8, 7, 1), Some(5));
+        assert_eq!(space.distance(3, 3, 1), Some(0));
+        for factor in [0, 1, 9, 99, 999_999] {
+            let pairs = (0..space.galaxies.len()).tuple_combinations();
+            let total: i64 = pairs.map(|(a, b)| space.distance(a, b, factor).unwrap()).sum();
+            assert_eq!(Some(total), space.total_distance(factor));
+        }
+        assert_eq!(space.distance(0, 6, usize::MAX), None);
     }
 
     #[test]
@@ -1,6 +1,9 @@
+use std::fmt::Write;
+
+use color_eyre::eyre::{bail, Result};
 use itertools::Itertools;
 
-use crate::solver::Solver;
+use crate::{solver::Solver, utils::memo::{Memo, MemoFn}};
 
 pub struct Day12;
 impl Solver for Day12 {
@@ -15,13 +18,12 @@ impl Solver for Day12 {
 
     #[allow(unused)]
     fn solve2(&self, input: &str) -> Option<i64> {
-        //Some(input.split("\n")
-        //    .filter(|s| !s.is_empty())
-        //    .map(Record::from)
-        //    .map(|r| r.unfold())
-        //    .map(|r| r.permutations() as i64)
-        //    .sum())
-        None
+        Some(input.split("\n")
+            .filter(|s| !s.is_empty())
+            .map(Record::from)
+            .map(|r| r.unfold(5))
+            .map(|r| r.permutations() as i64)
+            .sum())
     }
 }
 
@@ -44,6 +46,20 @@ impl From<char> for Spring {
     }
 }
 
+impl From<Spring> for char {
+    fn from(value: Spring) -> Self {
+        match value {
+            Spring::Operational => '.',
+            Spring::Damaged => '#',
+            Spring::Unknown => '?',
+        }
+    }
+}
+
+/// Progress through a record: (spring index, group index, length of the broken run ending at the
+/// previous spring).
+type State = (usize, usize, u32);
+
 #[derive(Debug)]
 struct Record {
     springs: Vec<Spring>,
@@ -60,94 +76,115 @@ impl From<&str> for Record {
 }
 
 impl Record {
-    //fn unfold(self) -> Self {
-    //    let mut unfolded_springs = Vec::with_capacity(5 * self.springs.len() + 4);
-    //    let mut unfolded_broken = Vec::with_capacity(5 * self.broken.len());
-    //    for i in 0..5 {
-    //        unfolded_springs.extend(self.springs.iter());
-    //        if i != 4 {
-    //            unfolded_springs.push(Spring::Unknown);
-    //        }
-    //        unfolded_broken.extend(self.broken.iter());
-    //    }
-
-    //    Self { springs: unfolded_springs, broken: unfolded_broken }
-    //}
-
-    fn unknown_count(&self) -> usize {
-        self.springs.iter().filter(|s| **s == Spring::Unknown).count()
-    }
-
-    fn permutations(&self) -> u32 {
-        let unknowns = self.unknown_count();
-        let mut valid = 0;
-        // iterate over possible resolutions of Unknowns
-        for bit_pattern in 0..(2u128.pow(unknowns as u32)) {
-            let mut block_index = 0;
-            let mut broken_seen = 0;
-            let mut bit_index = 0;
-            for spring in &self.springs {
-                match spring {
-                    Spring::Operational => {
-                        if broken_seen > 0 {
-                            if let Some(count) = self.broken.get(block_index) {
-                                if *count == broken_seen {
-                                    // Current count does match, reset for the next block
-                                    block_index += 1;
-                                    broken_seen = 0;
-                                } else {
-                                    // Current count does not match, check next pattern
-                                    break;
-                                }
-                            } else {
-                                // We have finished a contiguous block of broken springs that is
-                                // not in the block list, because the current index is larger
-                                break;
-                            }
-                        }
-                    },
-                    Spring::Damaged => {
-                        broken_seen += 1;
-                    },
-                    Spring::Unknown => {
-                        if ((bit_pattern >> bit_index) & 1) != 0 {
-                            bit_index += 1;
-                            broken_seen += 1;
-                        } else {
-                            bit_index += 1;
-                            // Copied from Spring::Operational block
-                            if broken_seen > 0 {
-                                if let Some(count) = self.broken.get(block_index) {
-                                    if *count == broken_seen {
-                                        // Current count does match, reset for the next block
-                                        block_index += 1;
-                                        broken_seen = 0;
-                                    } else {
-                                        // Current count does not match, check next pattern
-                                        break;
-                                    }
-                                } else {
-                                    // We have finished a contiguous block of broken springs that is
-                                    // not in the block list, because the current index is larger
-                                    break;
-                                }
-                            }
-                        }
-                    },
-                }
+    /// Repeat the springs `factor` times with an unknown spring between each copy, and repeat the
+    /// list of broken groups to match.
+    fn unfold(&self, factor: usize) -> Self {
+        let mut unfolded_springs = Vec::with_capacity(factor * (self.springs.len() + 1));
+        let mut unfolded_broken = Vec::with_capacity(factor * self.broken.len());
+        for i in 0..factor {
+            unfolded_springs.extend(self.springs.iter());
+            if i != factor - 1 {
+                unfolded_springs.push(Spring::Unknown);
             }
-            if broken_seen == 0 && block_index == self.broken.len() {
-                valid += 1;
-            } else {
-                if let Some(count) = self.broken.get(block_index) {
-                    if *count == broken_seen && block_index == self.broken.len() - 1 {
-                        valid += 1;
+            unfolded_broken.extend(self.broken.iter());
+        }
+
+        Self { springs: unfolded_springs, broken: unfolded_broken }
+    }
+
+    /// Count the ways of resolving the unknown springs that match the list of broken groups.
+    fn permutations(&self) -> u64 {
+        self.counter().get((0, 0, 0))
+    }
+
+    /// Lazily list every way of resolving the unknown springs that matches the list of broken
+    /// groups, in the same order as the springs would sort with '.' before '#'.
+    fn arrangements(&self) -> impl Iterator<Item = Vec<Spring>> + '_ {
+        let mut counts = self.counter();
+        // Depth first search, only following states that lead to at least one arrangement.
+        let mut stack = vec![];
+        if counts.get((0, 0, 0)) > 0 {
+            stack.push(((0, 0, 0), Vec::with_capacity(self.springs.len())));
+        }
+        std::iter::from_fn(move || {
+            while let Some((state, resolved)) = stack.pop() {
+                if state.0 == self.springs.len() {
+                    return Some(resolved);
+                }
+                for (spring, next) in self.next_states(state).into_iter().flatten().rev() {
+                    if counts.get(next) > 0 {
+                        let mut resolved = resolved.clone();
+                        resolved.push(spring);
+                        stack.push((next, resolved));
                     }
                 }
             }
+            None
+        })
+    }
+
+    /// Cached count of the ways to resolve the remaining springs from a state.
+    fn counter(&self) -> Memo<State, u64, impl MemoFn<State, u64> + '_> {
+        Memo::new(move |count, state: State| {
+            let (spring, group, run) = state;
+            if spring == self.springs.len() {
+                // Reached the end, every group must be complete
+                let complete = (group == self.broken.len() && run == 0)
+                    || (group + 1 == self.broken.len() && run == self.broken[group]);
+                return complete as u64;
+            }
+            self.next_states(state)
+                .into_iter()
+                .flatten()
+                .map(|(_, next)| count(next))
+                .sum()
+        })
+    }
+
+    /// The states reached by treating the current spring as operational, then as damaged, if that
+    /// is consistent with the record so far.
+    fn next_states(&self, (spring, group, run): State) -> [Option<(Spring, State)>; 2] {
+        let current = self.springs[spring];
+        let mut operational = None;
+        if current != Spring::Damaged {
+            if run == 0 {
+                operational = Some((Spring::Operational, (spring + 1, group, 0)));
+            } else if run == self.broken[group] {
+                // End of a run, which must match the group exactly
+                operational = Some((Spring::Operational, (spring + 1, group + 1, 0)));
+            }
+        }
+        let mut damaged = None;
+        if current != Spring::Operational {
+            // Extend the current run, as long as it doesn't get longer than the group
+            if self.broken.get(group).is_some_and(|&len| run < len) {
+                damaged = Some((Spring::Damaged, (spring + 1, group, run + 1)));
+            }
+        }
+        [operational, damaged]
+    }
+}
+
+/// List up to `count` arrangements for each record in the input, or just the record on `line`
+/// (counting from 1), with each unknown spring resolved to '#' or '.'.
+pub fn describe_arrangements(input: &str, line: Option<usize>, count: usize, unfold: usize) -> Result<String> {
+    let lines = input.lines().filter(|s| !s.is_empty()).collect_vec();
+    let selected = match line {
+        Some(n) if n == 0 || n > lines.len() => {
+            bail!("Line {} is out of range, the input has {} records", n, lines.len())
+        }
+        Some(n) => vec![(n, lines[n - 1])],
+        None => lines.into_iter().enumerate().map(|(i, l)| (i + 1, l)).collect(),
+    };
+    let mut out = String::new();
+    for (n, text) in selected {
+        let record = Record::from(text).unfold(unfold);
+        writeln!(out, "Line {}: {} ({} arrangements)", n, text, record.permutations())?;
+        for arrangement in record.arrangements().take(count) {
+            writeln!(out, "  {}", arrangement.into_iter().map(char::from).collect::<String>())?;
         }
-        valid
     }
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -174,16 +211,53 @@ mod tests {
         assert_eq!(Record::from("?###???????? 3,2,1").permutations(), 10);
     }
 
-//    #[test]
-//    fn part2() {
-//        let input = r#"???.### 1,1,3
-//.??..??...?##. 1,1,3
-//?#?#?#?#?#?#?#? 1,3,1,6
-//????.#...#... 4,1,1
-//????.######..#####. 1,6,5
-//?###???????? 3,2,1
-//"#;
-//        let output = Day12.solve2(input);
-//        assert_eq!(output, Some(525152));
-//    }
+    #[test]
+    fn test_arrangements() {
+        let record = Record::from("?###???????? 3,2,1");
+        let arrangements = record
+            .arrangements()
+            .map(|a| a.into_iter().map(char::from).collect::<String>())
+            .collect_vec();
+        assert_eq!(arrangements.len(), 10);
+        assert_eq!(arrangements[0], ".###....##.#");
+        assert_eq!(arrangements[9], ".###.##.#...");
+        assert!(arrangements.iter().all_unique());
+        assert!(arrangements.iter().tuple_windows().all(|(a, b)| a.replace('.', " ") < b.replace('.', " ")));
+
+        assert_eq!(Record::from("#.# 3").arrangements().count(), 0);
+        // Only as much is resolved as is asked for
+        let unfolded = Record::from("?###???????? 3,2,1").unfold(5);
+        assert_eq!(unfolded.arrangements().take(3).count(), 3);
+    }
+
+    #[test]
+    fn test_describe_arrangements() {
+        let input = "???.### 1,1,3\n.??..??...?##. 1,1,3\n";
+        let output = describe_arrangements(input, Some(2), 2, 1).unwrap();
+        assert_eq!(output, "Line 2: .??..??...?##. 1,1,3 (4 arrangements)\n  ..#...#...###.\n  ..#..#....###.\n");
+        assert!(describe_arrangements(input, Some(3), 2, 1).is_err());
+    }
+
+    #[test]
+    fn test_unfold() {
+        let record = Record::from(".# 1").unfold(5);
+        assert_eq!(record.springs.len(), 14);
+        assert_eq!(record.broken, vec![1; 5]);
+        assert_eq!(Record::from("???.### 1,1,3").unfold(5).permutations(), 1);
+        assert_eq!(Record::from("?###???????? 3,2,1").unfold(5).permutations(), 506250);
+        assert_eq!(Record::from("?###???????? 3,2,1").unfold(1).permutations(), 10);
+    }
+
+    #[test]
+    fn part2() {
+        let input = r#"???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1
+"#;
+        let output = Day12.solve2(input);
+        assert_eq!(output, Some(525152));
+    }
 }
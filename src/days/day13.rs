@@ -1,3 +1,6 @@
+use std::fmt::Write;
+
+use color_eyre::eyre::Result;
 use itertools::Itertools;
 
 use crate::solver::Solver;
@@ -6,37 +9,202 @@ pub struct Day13;
 impl Solver for Day13 {
     #[allow(unused)]
     fn solve1(&self, input: &str) -> Option<i64> {
-        let sections = parse_input(input);
-        Some(
-            sections
-                .into_iter()
-                .enumerate()
-                .map(|(i, s)| {
-                    find_vertical_rfln(&s, 0)
-                        .or_else(|| find_horizontal_rfln(&s, 0).map(|n| n * 100))
-                        .expect(&format!("{}: {:?}", i, s))
-                })
-                .sum(),
-        )
+        parse_input(input)
+            .iter()
+            .map(|s| perfect_reflection(&pattern(s).reflections()).map(Reflection::summary))
+            .sum()
     }
 
     #[allow(unused)]
     fn solve2(&self, input: &str) -> Option<i64> {
-        let sections = parse_input(input);
-        Some(
-            sections
-                .into_iter()
-                .enumerate()
-                .map(|(i, s)| {
-                    find_vertical_rfln(&s, 1)
-                        .or_else(|| find_horizontal_rfln(&s, 1).map(|n| n * 100))
-                        .expect(&format!("{}: {:?}", i, s))
+        parse_input(input)
+            .iter()
+            .map(|s| smudged_reflection(&pattern(s).reflections()).map(Reflection::summary))
+            .sum()
+    }
+}
+
+/// Which way a mirror runs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Axis {
+    Vertical,
+    Horizontal,
+}
+
+/// A candidate mirror line, and how many cells don't match their reflection across it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Reflection {
+    axis: Axis,
+    /// Number of columns left of a vertical mirror, or rows above a horizontal one.
+    position: usize,
+    mismatches: u32,
+}
+
+impl Reflection {
+    fn summary(&self) -> i64 {
+        match self.axis {
+            Axis::Vertical => self.position as i64,
+            Axis::Horizontal => self.position as i64 * 100,
+        }
+    }
+}
+
+/// The mirror line that reflects the pattern exactly, as in part 1.
+fn perfect_reflection(reflections: &[Reflection]) -> Option<&Reflection> {
+    reflections.iter().find(|r| r.mismatches == 0)
+}
+
+/// The mirror line that reflects the pattern once a single smudge is fixed, as in part 2. This is
+/// never the part 1 line, which needs no fixes. If several lines are one fix away, vertical lines
+/// come first, then the one nearest the left or top.
+fn smudged_reflection(reflections: &[Reflection]) -> Option<&Reflection> {
+    reflections.iter().find(|r| r.mismatches == 1)
+}
+
+/// Mirror lines of a pattern, whatever size of [`Line`] it's packed into.
+trait Mirrors {
+    /// Every possible mirror line in the pattern, vertical ones first, with the number of cells
+    /// that would have to change for it to be a real reflection.
+    fn reflections(&self) -> Vec<Reflection>;
+
+    /// Pairs of (x, y) cells that differ across the mirror. Either one could be the smudge.
+    fn smudges(&self, reflection: &Reflection) -> Vec<[(usize, usize); 2]>;
+}
+
+/// A pattern with each row and column packed into a [`Line`].
+struct Pattern<L> {
+    rows: Vec<L>,
+    columns: Vec<L>,
+}
+
+impl<L: Line> Pattern<L> {
+    fn new(section: &[Vec<Ground>]) -> Self {
+        let rocks = |cells: &mut dyn Iterator<Item = Ground>| L::from_rocks(cells.map(|g| g == Ground::Rock));
+        let rows = section.iter().map(|row| rocks(&mut row.iter().copied())).collect();
+        let columns = (0..section.first().map_or(0, |row| row.len()))
+            .map(|x| rocks(&mut section.iter().map(|row| row[x])))
+            .collect();
+        Self { rows, columns }
+    }
+
+    /// The lines a mirror on `axis` reflects onto each other.
+    fn lines(&self, axis: Axis) -> &[L] {
+        match axis {
+            Axis::Vertical => &self.columns,
+            Axis::Horizontal => &self.rows,
+        }
+    }
+}
+
+impl<L: Line> Mirrors for Pattern<L> {
+    fn reflections(&self) -> Vec<Reflection> {
+        [Axis::Vertical, Axis::Horizontal]
+            .into_iter()
+            .flat_map(|axis| {
+                let lines = self.lines(axis);
+                (1..lines.len()).map(move |position| Reflection {
+                    axis,
+                    position,
+                    mismatches: mirrored_pairs(lines.len(), position)
+                        .map(|(a, b)| lines[a].differences(&lines[b]))
+                        .sum(),
                 })
-                .sum(),
-        )
+            })
+            .collect()
+    }
+
+    fn smudges(&self, reflection: &Reflection) -> Vec<[(usize, usize); 2]> {
+        let axis = reflection.axis;
+        let lines = self.lines(axis);
+        mirrored_pairs(lines.len(), reflection.position)
+            .flat_map(|(a, b)| {
+                lines[a].differing(&lines[b]).into_iter().map(move |i| match axis {
+                    Axis::Vertical => [(a, i), (b, i)],
+                    Axis::Horizontal => [(i, a), (i, b)],
+                })
+            })
+            .collect()
+    }
+}
+
+/// Pack the pattern into the narrowest lines that hold its rows and columns.
+fn pattern(section: &[Vec<Ground>]) -> Box<dyn Mirrors> {
+    let width = section.first().map_or(0, |row| row.len());
+    match width.max(section.len()) {
+        0..=64 => Box::new(Pattern::<u64>::new(section)),
+        65..=128 => Box::new(Pattern::<u128>::new(section)),
+        _ => Box::new(Pattern::<Vec<u64>>::new(section)),
+    }
+}
+
+/// Indexes of the pairs of lines reflected onto each other by a mirror after the first
+/// `position` of `len` lines.
+fn mirrored_pairs(len: usize, position: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..position).rev().zip(position..len)
+}
+
+/// A row or column of a pattern, with a bit set for each rock.
+trait Line {
+    fn from_rocks(rocks: impl Iterator<Item = bool>) -> Self;
+
+    /// Number of cells that differ between the two lines.
+    fn differences(&self, other: &Self) -> u32;
+
+    /// Indexes of the cells that differ between the two lines.
+    fn differing(&self, other: &Self) -> Vec<usize>;
+}
+
+macro_rules! impl_line {
+    ($($t:ty),*) => {
+        $(
+            impl Line for $t {
+                fn from_rocks(rocks: impl Iterator<Item = bool>) -> Self {
+                    rocks.enumerate().fold(0, |line, (i, rock)| line | (rock as $t) << i)
+                }
+
+                fn differences(&self, other: &Self) -> u32 {
+                    (self ^ other).count_ones()
+                }
+
+                fn differing(&self, other: &Self) -> Vec<usize> {
+                    set_bits((self ^ other) as u128)
+                }
+            }
+        )*
+    };
+}
+
+impl_line!(u64, u128);
+
+/// Lines too wide for a u128, split into 64 cell chunks.
+impl Line for Vec<u64> {
+    fn from_rocks(rocks: impl Iterator<Item = bool>) -> Self {
+        rocks.chunks(64).into_iter().map(u64::from_rocks).collect()
+    }
+
+    fn differences(&self, other: &Self) -> u32 {
+        self.iter().zip(other).map(|(a, b)| a.differences(b)).sum()
+    }
+
+    fn differing(&self, other: &Self) -> Vec<usize> {
+        self.iter()
+            .zip(other)
+            .enumerate()
+            .flat_map(|(chunk, (a, b))| a.differing(b).into_iter().map(move |i| chunk * 64 + i))
+            .collect()
     }
 }
 
+/// Indexes of the set bits, lowest first.
+fn set_bits(mut bits: u128) -> Vec<usize> {
+    let mut indexes = vec![];
+    while bits != 0 {
+        indexes.push(bits.trailing_zeros() as usize);
+        bits &= bits - 1;
+    }
+    indexes
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 enum Ground {
     Ash,
@@ -64,70 +232,36 @@ fn parse_section(input: &str) -> Vec<Vec<Ground>> {
         .collect_vec()
 }
 
-fn find_vertical_rfln(section: &Vec<Vec<Ground>>, smudge_count: u32) -> Option<i64> {
-    // Iterate over each non-edge position
-    for column in 1..(section[0].len()) {
-        let mut fixed_smudges = 0;
-        let mut row = 0;
-        'row: loop {
-            if row == section.len() {
-                if fixed_smudges == smudge_count {
-                    return Some(column as i64);
-                }
-                break;
-            }
-            let mut steps = 0i64;
-            loop {
-                let left = column as i64 - steps - 1;
-                let right = column as i64 + steps;
-                if left < 0 || right == section[0].len() as i64 {
-                    row += 1;
-                    break;
-                }
-                if section[row][left as usize] != section[row][right as usize] {
-                    if fixed_smudges == smudge_count {
-                        break 'row;
-                    }
-                    fixed_smudges += 1;
-                }
-                steps += 1;
+/// Every mirror line in each pattern that's at most `max_mismatches` fixes away from being a real
+/// reflection, with the pairs of cells that would need fixing.
+pub fn describe_reflections(input: &str, max_mismatches: u32) -> Result<String> {
+    let mut out = String::new();
+    for (n, section) in parse_input(input).iter().enumerate() {
+        writeln!(out, "Pattern {}:", n + 1)?;
+        let pattern = pattern(section);
+        for reflection in pattern.reflections().iter().filter(|r| r.mismatches <= max_mismatches) {
+            let line = match reflection.axis {
+                Axis::Vertical => "vertical line after column",
+                Axis::Horizontal => "horizontal line after row",
+            };
+            let plural = if reflection.mismatches == 1 { "" } else { "es" };
+            write!(
+                out,
+                "  {} {}: {} mismatch{}",
+                line, reflection.position, reflection.mismatches, plural
+            )?;
+            let smudges = pattern
+                .smudges(reflection)
+                .iter()
+                .map(|[a, b]| format!("{:?} or {:?}", a, b))
+                .join(", ");
+            if !smudges.is_empty() {
+                write!(out, ", fix {}", smudges)?;
             }
+            writeln!(out)?;
         }
     }
-    None
-}
-
-fn find_horizontal_rfln(section: &Vec<Vec<Ground>>, smudge_count: u32) -> Option<i64> {
-    // Iterate over each non-edge position
-    for row in 1..(section.len()) {
-        let mut fixed_smudges = 0;
-        let mut column = 0;
-        'column: loop {
-            if column == section[0].len() {
-                if fixed_smudges == smudge_count {
-                    return Some(row as i64);
-                }
-                break;
-            }
-            let mut steps = 0i64;
-            loop {
-                let up = row as i64 - steps - 1;
-                let down = row as i64 + steps;
-                if up < 0 || down == section.len() as i64 {
-                    column += 1;
-                    break;
-                }
-                if section[up as usize][column] != section[down as usize][column] {
-                    if fixed_smudges == smudge_count {
-                        break 'column;
-                    }
-                    fixed_smudges += 1;
-                }
-                steps += 1;
-            }
-        }
-    }
-    None
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -195,6 +329,102 @@ mod tests {
         assert_eq!(output, Some(14));
     }
 
+    #[test]
+    fn test_reflections() {
+        let sections = parse_input(EXAMPLE);
+        let pattern_1 = pattern(&sections[0]);
+        let first = pattern_1.reflections();
+        assert_eq!(first.len(), 8 + 6);
+        let perfect = perfect_reflection(&first).unwrap();
+        assert_eq!((perfect.axis, perfect.position), (Axis::Vertical, 5));
+        let smudged = smudged_reflection(&first).unwrap();
+        assert_eq!((smudged.axis, smudged.position), (Axis::Horizontal, 3));
+        assert_eq!(pattern_1.smudges(smudged), vec![[(0, 0), (0, 5)]]);
+
+        let pattern_2 = pattern(&sections[1]);
+        let second = pattern_2.reflections();
+        let perfect = perfect_reflection(&second).unwrap();
+        assert_eq!((perfect.axis, perfect.position), (Axis::Horizontal, 4));
+        let smudged = smudged_reflection(&second).unwrap();
+        assert_eq!((smudged.axis, smudged.position), (Axis::Horizontal, 1));
+        assert_eq!(pattern_2.smudges(smudged), vec![[(4, 0), (4, 1)]]);
+        // Every other line needs more than one change
+        assert!(second.iter().filter(|r| r.mismatches < 2).count() == 2);
+        let worst = second.iter().max_by_key(|r| r.mismatches).unwrap();
+        assert_eq!(pattern_2.smudges(worst).len(), worst.mismatches as usize);
+    }
+
+    #[test]
+    fn test_several_smudged_lines() {
+        // Both the vertical line and the lower horizontal line are one fix away, and the vertical
+        // one comes first.
+        let input = "##\n##\n#.\n";
+        let pattern = pattern(&parse_section(input));
+        let all = pattern.reflections();
+        assert_eq!(perfect_reflection(&all).map(Reflection::summary), Some(100));
+        let smudged = smudged_reflection(&all).unwrap();
+        assert_eq!((smudged.axis, smudged.position), (Axis::Vertical, 1));
+        assert_eq!(pattern.smudges(smudged), vec![[(0, 2), (1, 2)]]);
+        let horizontal = all.iter().find(|r| r.axis == Axis::Horizontal && r.position == 2);
+        assert_eq!(horizontal.unwrap().mismatches, 1);
+    }
+
+    #[test]
+    fn test_describe_reflections() {
+        let output = describe_reflections(EXAMPLE, 1).unwrap();
+        assert_eq!(
+            output,
+            "Pattern 1:\n  vertical line after column 5: 0 mismatches\n  \
+             horizontal line after row 3: 1 mismatch, fix (0, 0) or (0, 5)\n\
+             Pattern 2:\n  horizontal line after row 1: 1 mismatch, fix (4, 0) or (4, 1)\n  \
+             horizontal line after row 4: 0 mismatches\n"
+        );
+    }
+
+    #[test]
+    fn test_lines() {
+        let rocks = |s: &str| s.chars().map(|c| c == '#').collect_vec();
+        let a = u64::from_rocks(rocks("#.##..##.").into_iter());
+        let b = u64::from_rocks(rocks("..#.##.#.").into_iter());
+        assert_eq!(a, 0b011001101);
+        assert_eq!(a.differences(&b), 5);
+        assert_eq!(a.differing(&b), vec![0, 3, 4, 5, 6]);
+        // Wider than a u128
+        let mut wide = rocks(&".".repeat(200));
+        let narrow = Vec::<u64>::from_rocks(wide.clone().into_iter());
+        wide[3] = true;
+        wide[150] = true;
+        let wide = Vec::<u64>::from_rocks(wide.into_iter());
+        assert_eq!(wide.len(), 4);
+        assert_eq!(wide.differences(&narrow), 2);
+        assert_eq!(wide.differing(&narrow), vec![3, 150]);
+    }
+
+    #[test]
+    fn test_wide_patterns() {
+        // Two rows mirrored down the middle, at widths that need each kind of line
+        for width in [20, 100, 300] {
+            let half = |seed: usize| {
+                (0..width / 2).map(|i| if (i * i + seed) % 7 < 3 { '#' } else { '.' }).collect::<String>()
+            };
+            let mirrored = |s: String| format!("{}{}", s, s.chars().rev().collect::<String>());
+            let mut rows = [mirrored(half(0)), mirrored(half(2))];
+            let middle = |rows: &[String]| {
+                let pattern = pattern(&parse_section(&rows.join("\n")));
+                let all = pattern.reflections();
+                assert_eq!(all.len(), width - 1 + 1);
+                let line = all.into_iter().find(|r| r.axis == Axis::Vertical && r.position == width / 2);
+                (line.unwrap().mismatches, pattern.smudges(&line.unwrap()))
+            };
+            assert_eq!(middle(&rows), (0, vec![]));
+
+            // Smudge the far right of the second row
+            let flipped = if rows[1].ends_with('#') { "." } else { "#" };
+            rows[1].replace_range(width - 1.., flipped);
+            assert_eq!(middle(&rows), (1, vec![[(0, 1), (width - 1, 1)]]));
+        }
+    }
+
     #[test]
     fn part2() {
         let output = Day13.solve2(EXAMPLE);
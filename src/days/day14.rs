@@ -1,8 +1,6 @@
-use std::{collections::HashMap, hash::BuildHasher, time::Instant};
-
 use itertools::Itertools;
 
-use crate::solver::Solver;
+use crate::{solver::Solver, utils::cycle};
 
 pub struct Day14;
 impl Solver for Day14 {
@@ -34,25 +32,15 @@ impl Solver for Day14 {
 
     #[allow(unused)]
     fn solve2(&self, input: &str) -> Option<i64> {
-        let mut platform = parse_platform(input);
-        let start = Instant::now();
-        let mut hashes = HashMap::new();
-        let hasher = std::collections::hash_map::RandomState::new();
-        for i in 1..=1_000_000_000 {
-            perform_roll(&mut platform);
-            let current_hash = hasher.hash_one(&platform);
-            if hashes.contains_key(&current_hash) {
-                let cycle_len = i - hashes.get(&current_hash).unwrap();
-                let cycles_til_end = 1_000_000_000 - i;
-                let additional_runs = cycles_til_end % cycle_len;
-                for _ in 0..additional_runs {
-                    perform_roll(&mut platform);
-                }
-                break;
-            } else {
-                hashes.insert(current_hash, i);
-            }
-        }
+        let platform = cycle::nth_state(
+            parse_platform(input),
+            |platform| {
+                let mut platform = platform.clone();
+                perform_roll(&mut platform);
+                platform
+            },
+            1_000_000_000,
+        );
         let height = platform.len();
         let mut load = 0;
         for row in 0..platform.len() {
@@ -2,15 +2,15 @@ mod day01;
 mod day02;
 mod day03;
 mod day04;
-mod day05;
+pub mod day05;
 mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
 mod day14;
 mod day15;
 mod day16;
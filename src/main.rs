@@ -1,11 +1,12 @@
 use std::{
     fs,
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use color_eyre::eyre::{bail, Result, Context};
-use days::days::get_solver;
+use days::{day05, day07, day08, day09, day10, day11, day12, day13, days::get_solver};
 
 mod days;
 mod solver;
@@ -14,6 +15,9 @@ mod utils;
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = Args::parse();
+    if let Some(command) = args.command {
+        return run_command(command);
+    }
     print_header();
     if let Some(day) = args.day {
         let input = read_input(day)?;
@@ -34,6 +38,52 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+fn run_command(command: Command) -> Result<()> {
+    match command {
+        Command::Convert { value, from, to, trace } => {
+            let input = read_input(5)?;
+            print!("{}", day05::describe_conversion(&input, value, &from, &to, trace)?);
+        }
+        Command::Explain { part } => {
+            let input = read_input(7)?;
+            print!("{}", day07::explain(&input, part)?);
+        }
+        Command::Dot { output } => {
+            let dot = day08::to_dot(&read_input(8)?)?;
+            match output {
+                Some(path) => fs::write(&path, dot)
+                    .wrap_err(format!("Failed to write {}", path.display()))?,
+                None => print!("{}", dot),
+            }
+        }
+        Command::Extrapolate { steps } => {
+            let input = read_input(9)?;
+            print!("{}", day09::describe_extrapolation(&input, steps)?);
+        }
+        Command::Render { format, output } => {
+            let rendered = day10::render(&read_input(10)?, format)?;
+            match output {
+                Some(path) => fs::write(&path, rendered)
+                    .wrap_err(format!("Failed to write {}", path.display()))?,
+                None => print!("{}", rendered),
+            }
+        }
+        Command::Expand { factor, render } => {
+            let input = read_input(11)?;
+            print!("{}", day11::describe_expansion(&input, factor, render)?);
+        }
+        Command::Arrangements { line, count, unfold } => {
+            let input = read_input(12)?;
+            print!("{}", day12::describe_arrangements(&input, line, count, unfold)?);
+        }
+        Command::Mirrors { max_mismatches } => {
+            let input = read_input(13)?;
+            print!("{}", day13::describe_reflections(&input, max_mismatches)?);
+        }
+    }
+    Ok(())
+}
+
 fn read_input(day: u8) -> Result<String> {
     fs::read_to_string(format!("./input/Day{}", day)).wrap_err(format!("Failed to read input for day {}", day))
 }
@@ -91,4 +141,75 @@ struct Args {
     day: Option<u8>,
     #[arg(short, long)]
     part: Option<u8>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Convert a value between Day 5 almanac categories
+    Convert {
+        value: i64,
+        /// Category the value is in
+        #[arg(short, long, default_value = "seed")]
+        from: String,
+        /// Category to convert the value to
+        #[arg(short, long, default_value = "location")]
+        to: String,
+        /// Show the value in every category along the way
+        #[arg(long)]
+        trace: bool,
+    },
+    /// Print the Day 7 hands in rank order with their types and winnings
+    Explain {
+        #[arg(short, long, default_value_t = 1)]
+        part: u8,
+    },
+    /// Export the Day 8 network as a Graphviz DOT graph
+    Dot {
+        /// File to write the graph to, instead of printing it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the degree of each Day 9 history and extrapolate it
+    Extrapolate {
+        /// Steps past the end of each history, or before the start if negative
+        #[arg(short, long, default_value_t = 1, allow_negative_numbers = true)]
+        steps: i64,
+    },
+    /// Draw the Day 10 pipe loop with the tiles it encloses
+    Render {
+        #[arg(short, long, value_enum, default_value_t = day10::RenderFormat::Ansi)]
+        format: day10::RenderFormat,
+        /// File to write the drawing to, instead of printing it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print where each Day 11 galaxy ends up after the universe expands
+    Expand {
+        /// Extra copies of each empty row and column, e.g. 999999 to make them a million times larger
+        #[arg(short, long, default_value_t = 1)]
+        factor: u64,
+        /// Also draw the expanded map, if it's small enough
+        #[arg(short, long)]
+        render: bool,
+    },
+    /// Print arrangements of the Day 12 springs that match each record's damaged groups
+    Arrangements {
+        /// Only show the record on this line of the input, counting from 1
+        #[arg(short, long)]
+        line: Option<usize>,
+        /// Number of arrangements to print for each record
+        #[arg(short = 'n', long, default_value_t = 10)]
+        count: usize,
+        /// Unfold each record this many times first, as in part 2
+        #[arg(short, long, default_value_t = 1)]
+        unfold: usize,
+    },
+    /// List the possible mirror lines in each Day 13 pattern and the smudges stopping them
+    Mirrors {
+        /// Only show lines needing at most this many fixes
+        #[arg(short, long, default_value_t = 1)]
+        max_mismatches: u32,
+    },
 }
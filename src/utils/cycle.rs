@@ -0,0 +1,158 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// The states visited by repeatedly applying a step function, up to the first repeated state.
+#[derive(Debug, Clone)]
+pub struct Cycle<S> {
+    /// Index of the first state that is part of the cycle.
+    pub start: usize,
+    /// Number of steps between repeats of a state in the cycle.
+    pub length: usize,
+    /// Every state from the initial state up to the end of the first loop around the cycle.
+    states: Vec<S>,
+}
+
+impl<S> Cycle<S> {
+    /// The state after `n` steps from the initial state.
+    pub fn state_at(&self, n: usize) -> &S {
+        &self.states[self.index_of(n)]
+    }
+
+    /// Index into the recorded states equivalent to step `n`.
+    fn index_of(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Run `step` from `initial` until a state repeats, comparing states exactly.
+pub fn find_cycle<S, F>(initial: S, step: F) -> Cycle<S>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    find_cycle_by_key(initial, step, S::clone)
+}
+
+/// Run `step` from `initial` until a state repeats, comparing states by `fingerprint`.
+/// The fingerprint must be exact (two states with the same fingerprint must behave identically),
+/// but can be much smaller than the state itself, e.g. just the positions of moving objects.
+pub fn find_cycle_by_key<S, K, F, FK>(initial: S, mut step: F, mut fingerprint: FK) -> Cycle<S>
+where
+    K: Eq + Hash,
+    F: FnMut(&S) -> S,
+    FK: FnMut(&S) -> K,
+{
+    let mut seen = HashMap::from([(fingerprint(&initial), 0)]);
+    let mut states = vec![initial];
+    loop {
+        let next = step(states.last().unwrap());
+        match seen.insert(fingerprint(&next), states.len()) {
+            Some(start) => {
+                let length = states.len() - start;
+                return Cycle {
+                    start,
+                    length,
+                    states,
+                };
+            }
+            None => states.push(next),
+        }
+    }
+}
+
+/// Find the cycle start and length using Brent's algorithm, which only keeps two states in memory
+/// at a time at the cost of running the step function more often.
+/// Returns `(start, length)`.
+#[allow(dead_code)]
+pub fn brent<S, F>(initial: S, mut step: F) -> (usize, usize)
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    // Find the cycle length by searching successive powers of two.
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // Find the start of the cycle by moving two pointers `length` apart until they meet.
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    (start, length)
+}
+
+/// The state after `n` steps from `initial`, skipping ahead once a cycle is found.
+pub fn nth_state<S, F>(initial: S, step: F, n: usize) -> S
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    find_cycle(initial, step).state_at(n).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 2 -> ...
+    fn step(n: &u32) -> u32 {
+        if *n == 6 {
+            2
+        } else {
+            n + 1
+        }
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let cycle = find_cycle(0, step);
+        assert_eq!(cycle.start, 2);
+        assert_eq!(cycle.length, 5);
+        assert_eq!(*cycle.state_at(0), 0);
+        assert_eq!(*cycle.state_at(6), 6);
+        assert_eq!(*cycle.state_at(7), 2);
+        assert_eq!(*cycle.state_at(1_000_000_000), 2 + (1_000_000_000 - 2) % 5);
+    }
+
+    #[test]
+    fn test_find_cycle_by_key() {
+        // Only the lowest bit matters for the next state, so it is an exact fingerprint.
+        let cycle = find_cycle_by_key((0u32, 0u32), |&(b, n)| (b ^ 1, n + 1), |&(b, _)| b);
+        assert_eq!(cycle.start, 0);
+        assert_eq!(cycle.length, 2);
+        assert_eq!(cycle.state_at(5).0, 1);
+    }
+
+    #[test]
+    fn test_brent() {
+        assert_eq!(brent(0, step), (2, 5));
+        assert_eq!(brent(4, step), (0, 5));
+    }
+
+    #[test]
+    fn test_nth_state() {
+        assert_eq!(nth_state(0, step, 3), 3);
+        assert_eq!(nth_state(0, step, 12), 2);
+    }
+}
@@ -0,0 +1,101 @@
+use num::Integer;
+
+/// Direction a polygon's vertices go around it, with the y axis pointing up.
+/// With the y axis pointing down (as when indexing rows of the input), the directions are swapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Twice the signed area of the polygon with the given vertices, using the shoelace formula.
+/// Positive when the vertices go counter-clockwise. The last vertex joins back to the first.
+pub fn signed_area_x2(vertices: &[(i64, i64)]) -> i64 {
+    edges(vertices)
+        .map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1)
+        .sum()
+}
+
+/// Area of the polygon, rounded down if it isn't a whole number.
+#[allow(dead_code)]
+pub fn area(vertices: &[(i64, i64)]) -> i64 {
+    signed_area_x2(vertices).abs() / 2
+}
+
+/// Number of integer points on the edges of the polygon, including the vertices.
+pub fn boundary_points(vertices: &[(i64, i64)]) -> i64 {
+    edges(vertices)
+        .map(|((x1, y1), (x2, y2))| (x2 - x1).abs().gcd(&(y2 - y1).abs()))
+        .sum()
+}
+
+/// Number of integer points strictly inside the polygon, using Pick's theorem.
+/// The polygon must be simple (no self intersections) and have integer vertices.
+pub fn interior_points(vertices: &[(i64, i64)]) -> i64 {
+    (signed_area_x2(vertices).abs() - boundary_points(vertices) + 2) / 2
+}
+
+/// Direction of the polygon's vertices, or None if it has no area.
+#[allow(dead_code)]
+pub fn winding(vertices: &[(i64, i64)]) -> Option<Winding> {
+    match signed_area_x2(vertices).signum() {
+        1 => Some(Winding::CounterClockwise),
+        -1 => Some(Winding::Clockwise),
+        _ => None,
+    }
+}
+
+/// Every edge of the polygon, including the one from the last vertex back to the first.
+fn edges(vertices: &[(i64, i64)]) -> impl Iterator<Item = ((i64, i64), (i64, i64))> + '_ {
+    vertices
+        .iter()
+        .copied()
+        .zip(vertices.iter().copied().cycle().skip(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square() {
+        let square = [(0, 0), (4, 0), (4, 4), (0, 4)];
+        assert_eq!(signed_area_x2(&square), 32);
+        assert_eq!(area(&square), 16);
+        assert_eq!(boundary_points(&square), 16);
+        assert_eq!(interior_points(&square), 9);
+        assert_eq!(winding(&square), Some(Winding::CounterClockwise));
+
+        let reversed = [(0, 4), (4, 4), (4, 0), (0, 0)];
+        assert_eq!(signed_area_x2(&reversed), -32);
+        assert_eq!(interior_points(&reversed), 9);
+        assert_eq!(winding(&reversed), Some(Winding::Clockwise));
+    }
+
+    #[test]
+    fn test_every_boundary_point_as_vertex() {
+        // A pipe loop lists every tile it passes through, not just the corners.
+        let mut vertices = vec![];
+        vertices.extend((1..7).map(|x| (x, 1)));
+        vertices.extend((1..7).map(|y| (7, y)));
+        vertices.extend((2..=7).rev().map(|x| (x, 7)));
+        vertices.extend((2..=7).rev().map(|y| (1, y)));
+        assert_eq!(boundary_points(&vertices), 24);
+        assert_eq!(interior_points(&vertices), 25);
+    }
+
+    #[test]
+    fn test_diagonal_edges() {
+        let triangle = [(0, 0), (4, 0), (0, 4)];
+        assert_eq!(signed_area_x2(&triangle), 16);
+        assert_eq!(boundary_points(&triangle), 12);
+        assert_eq!(interior_points(&triangle), 3);
+    }
+
+    #[test]
+    fn test_degenerate() {
+        assert_eq!(winding(&[(0, 0), (2, 2)]), None);
+        assert_eq!(signed_area_x2(&[]), 0);
+    }
+}
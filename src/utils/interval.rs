@@ -0,0 +1,318 @@
+use std::fmt::Display;
+
+/// Half-open interval of integers, `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    pub fn new(start: i64, end: i64) -> Self {
+        Self { start, end }
+    }
+
+    pub fn from_len(start: i64, len: i64) -> Self {
+        Self::new(start, start + len)
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> i64 {
+        (self.end - self.start).max(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    /// Overlapping part of both intervals, if there is one.
+    pub fn intersect(&self, other: &Interval) -> Option<Interval> {
+        let overlap = Interval::new(self.start.max(other.start), self.end.min(other.end));
+        (!overlap.is_empty()).then_some(overlap)
+    }
+
+    pub fn shift(&self, offset: i64) -> Interval {
+        Interval::new(self.start + offset, self.end + offset)
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {})", self.start, self.end)
+    }
+}
+
+/// Set of integers stored as sorted, disjoint, non-adjacent intervals.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[allow(dead_code)]
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Total number of integers in the set.
+    #[allow(dead_code)]
+    pub fn len(&self) -> i64 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    #[allow(dead_code)]
+    pub fn max(&self) -> Option<i64> {
+        self.intervals.last().map(|i| i.end - 1)
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, value: i64) -> bool {
+        let i = self.intervals.partition_point(|i| i.end <= value);
+        self.intervals.get(i).is_some_and(|i| i.contains(value))
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        if interval.is_empty() {
+            return;
+        }
+        // Intervals in [first, last) touch the new one and get merged into it.
+        let first = self.intervals.partition_point(|i| i.end < interval.start);
+        let last = self.intervals.partition_point(|i| i.start <= interval.end);
+        let merged = self.intervals[first..last].iter().fold(interval, |acc, i| {
+            Interval::new(acc.start.min(i.start), acc.end.max(i.end))
+        });
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut out = self.clone();
+        for interval in &other.intervals {
+            out.insert(*interval);
+        }
+        out
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut out = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            if let Some(overlap) = a.intersect(&b) {
+                out.push(overlap);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { intervals: out }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut out = vec![];
+        let mut j = 0;
+        for interval in &self.intervals {
+            let mut start = interval.start;
+            // Skip intervals of `other` entirely before this one
+            while j < other.intervals.len() && other.intervals[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.intervals.len() && other.intervals[k].start < interval.end {
+                let cut = other.intervals[k];
+                if cut.start > start {
+                    out.push(Interval::new(start, cut.start));
+                }
+                start = start.max(cut.end);
+                k += 1;
+            }
+            if start < interval.end {
+                out.push(Interval::new(start, interval.end));
+            }
+        }
+        IntervalSet { intervals: out }
+    }
+
+    pub fn shift(&self, offset: i64) -> IntervalSet {
+        IntervalSet {
+            intervals: self.intervals.iter().map(|i| i.shift(offset)).collect(),
+        }
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(value: Interval) -> Self {
+        Self::from_iter([value])
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = Interval>>(iter: T) -> Self {
+        let mut set = Self::new();
+        for interval in iter {
+            set.insert(interval);
+        }
+        set
+    }
+}
+
+impl Display for IntervalSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{")?;
+        for (i, interval) in self.intervals.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", interval)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Piecewise mapping that shifts values in each source interval by an offset, and leaves values
+/// outside every source interval unchanged.
+/// When source intervals overlap, the one added first takes priority.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeMap {
+    /// Source intervals with the offset to apply, in priority order.
+    pieces: Vec<(Interval, i64)>,
+}
+
+impl RangeMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map `source` onto the interval of the same length starting at `dest`.
+    pub fn insert(&mut self, source: Interval, dest: i64) {
+        self.pieces.push((source, dest - source.start));
+    }
+
+    #[allow(dead_code)]
+    pub fn pieces(&self) -> &[(Interval, i64)] {
+        &self.pieces
+    }
+
+    pub fn get(&self, value: i64) -> i64 {
+        self.pieces
+            .iter()
+            .find(|(source, _)| source.contains(value))
+            .map(|(_, offset)| value + offset)
+            .unwrap_or(value)
+    }
+
+    /// Split `set` along the source intervals, and map every part of it at once.
+    pub fn apply(&self, set: &IntervalSet) -> IntervalSet {
+        let mut remaining = set.clone();
+        let mut out = IntervalSet::new();
+        for (source, offset) in &self.pieces {
+            let hit = remaining.intersection(&IntervalSet::from(*source));
+            remaining = remaining.difference(&hit);
+            out = out.union(&hit.shift(*offset));
+        }
+        out.union(&remaining)
+    }
+
+    /// Every value that maps into `set`.
+    pub fn preimage(&self, set: &IntervalSet) -> IntervalSet {
+        let mut covered = IntervalSet::new();
+        let mut out = IntervalSet::new();
+        for (source, offset) in &self.pieces {
+            // Only the part of the source not claimed by an earlier piece uses this offset.
+            let effective = IntervalSet::from(*source).difference(&covered);
+            out = out.union(&set.shift(-offset).intersection(&effective));
+            covered.insert(*source);
+        }
+        out.union(&set.difference(&covered))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(i64, i64)]) -> IntervalSet {
+        intervals
+            .iter()
+            .map(|&(s, e)| Interval::new(s, e))
+            .collect()
+    }
+
+    #[test]
+    fn test_interval() {
+        let a = Interval::from_len(5, 3);
+        assert_eq!(a, Interval::new(5, 8));
+        assert_eq!(a.len(), 3);
+        assert!(a.contains(7));
+        assert!(!a.contains(8));
+        assert_eq!(
+            a.intersect(&Interval::new(7, 20)),
+            Some(Interval::new(7, 8))
+        );
+        assert_eq!(a.intersect(&Interval::new(8, 20)), None);
+    }
+
+    #[test]
+    fn test_insert_merges() {
+        let s = set(&[(10, 12), (1, 3), (5, 7), (3, 4)]);
+        assert_eq!(
+            s.intervals(),
+            &[
+                Interval::new(1, 4),
+                Interval::new(5, 7),
+                Interval::new(10, 12)
+            ]
+        );
+        let s = s.union(&set(&[(4, 5), (6, 11)]));
+        assert_eq!(s.intervals(), &[Interval::new(1, 12)]);
+        assert_eq!(s.len(), 11);
+        assert!(s.contains(11));
+        assert!(!s.contains(12));
+    }
+
+    #[test]
+    fn test_intersection_difference() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25), (28, 40)]);
+        assert_eq!(a.intersection(&b), set(&[(5, 10), (20, 25), (28, 30)]));
+        assert_eq!(a.difference(&b), set(&[(0, 5), (25, 28)]));
+        assert_eq!(b.difference(&a), set(&[(10, 20), (30, 40)]));
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn test_range_map() {
+        // seed-to-soil map from the Day 5 example
+        let mut map = RangeMap::new();
+        map.insert(Interval::from_len(98, 2), 50);
+        map.insert(Interval::from_len(50, 48), 52);
+        assert_eq!(map.get(79), 81);
+        assert_eq!(map.get(99), 51);
+        assert_eq!(map.get(10), 10);
+
+        let mapped = map.apply(&set(&[(45, 55), (95, 101)]));
+        assert_eq!(mapped, set(&[(45, 50), (50, 52), (52, 57), (97, 101)]));
+        assert_eq!(mapped.len(), 16);
+
+        assert_eq!(map.preimage(&set(&[(50, 53)])), set(&[(50, 51), (98, 100)]));
+        assert_eq!(map.preimage(&mapped), set(&[(45, 55), (95, 101)]));
+    }
+}
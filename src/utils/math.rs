@@ -0,0 +1,295 @@
+use std::ops::RangeInclusive;
+
+use color_eyre::eyre::{eyre, Result};
+use num::{Integer, NumCast, PrimInt, Signed};
+
+/// Any signed primitive integer, e.g. i64 or i128.
+pub trait Int: PrimInt + Signed + Integer {}
+
+impl<T: PrimInt + Signed + Integer> Int for T {}
+
+/// Greatest common divisor of every value. Zero for an empty iterator.
+#[allow(dead_code)]
+pub fn gcd_all<T: Int, I: IntoIterator<Item = T>>(values: I) -> T {
+    values.into_iter().fold(T::zero(), |acc, n| acc.gcd(&n))
+}
+
+/// Least common multiple of every value, or None if it overflows. One for an empty iterator.
+pub fn lcm_all<T: Int, I: IntoIterator<Item = T>>(values: I) -> Option<T> {
+    values.into_iter().try_fold(T::one(), |acc, n| {
+        if n.is_zero() {
+            return Some(T::zero());
+        }
+        (acc / acc.gcd(&n)).checked_mul(&n.abs())
+    })
+}
+
+/// Extended Euclidean algorithm. Returns `(g, x, y)` such that `a * x + b * y == g`, where `g` is
+/// the (non-negative) greatest common divisor of `a` and `b`.
+pub fn ext_gcd<T: Int>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::one(), T::zero());
+    let (mut old_y, mut y) = (T::zero(), T::one());
+    while !r.is_zero() {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r.is_negative() {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// `(a * b) mod m`, in the range `0..m`, without overflowing even when `a * b` would.
+/// None if `m` isn't positive.
+pub fn mul_mod<T: Int>(a: T, b: T, m: T) -> Option<T> {
+    if !m.is_positive() {
+        return None;
+    }
+    let (a, b) = (a.mod_floor(&m), b.mod_floor(&m));
+    if let Some(product) = a.checked_mul(&b) {
+        return Some(product.mod_floor(&m));
+    }
+    // Double-and-add, keeping every intermediate value below m.
+    let add_mod = |x: T, y: T| if x >= m - y { x - (m - y) } else { x + y };
+    let (mut result, mut a, mut b) = (T::zero(), a, b);
+    while !b.is_zero() {
+        if b.is_odd() {
+            result = add_mod(result, a);
+        }
+        a = add_mod(a, a);
+        b = b >> 1;
+    }
+    Some(result)
+}
+
+/// `base.pow(exp) mod m`, in the range `0..m`. None if `m` isn't positive.
+#[allow(dead_code)]
+pub fn mod_pow<T: Int>(base: T, exp: T, m: T) -> Option<T> {
+    if !m.is_positive() {
+        return None;
+    }
+    let mut result = T::one().mod_floor(&m);
+    let (mut base, mut exp) = (base.mod_floor(&m), exp);
+    while exp.is_positive() {
+        if exp.is_odd() {
+            result = mul_mod(result, base, m)?;
+        }
+        base = mul_mod(base, base, m)?;
+        exp = exp >> 1;
+    }
+    Some(result)
+}
+
+/// Multiplicative inverse of `a` modulo `m`, if `m` is positive and `a` and `m` are coprime.
+#[allow(dead_code)]
+pub fn mod_inv<T: Int>(a: T, m: T) -> Option<T> {
+    if !m.is_positive() {
+        return None;
+    }
+    let (g, x, _) = ext_gcd(a.mod_floor(&m), m);
+    g.is_one().then(|| x.mod_floor(&m))
+}
+
+/// Solve a system of congruences `x ≡ r (mod m)` with the Chinese Remainder Theorem. The moduli
+/// don't need to be coprime.
+/// Returns `(x, lcm)` where `x` is the smallest non-negative solution and every solution is
+/// `x + k * lcm`, or None if a modulus isn't positive, the congruences are incompatible or the
+/// combined modulus overflows.
+pub fn crt<T: Int, I: IntoIterator<Item = (T, T)>>(congruences: I) -> Option<(T, T)> {
+    congruences
+        .into_iter()
+        .try_fold((T::zero(), T::one()), |(r1, m1), (r2, m2)| {
+            if !m2.is_positive() {
+                return None;
+            }
+            let (g, p, _) = ext_gcd(m1, m2);
+            let diff = r2 - r1;
+            if !(diff % g).is_zero() {
+                return None;
+            }
+            let lcm = (m1 / g).checked_mul(&m2)?;
+            // k * m1 ≡ diff (mod m2), so k ≡ (diff / g) * p (mod m2 / g)
+            let m2g = m2 / g;
+            let k = mul_mod(diff / g, p, m2g)?;
+            let x = r1.checked_add(&m1.checked_mul(&k)?)?.mod_floor(&lcm);
+            Some((x, lcm))
+        })
+}
+
+/// Floor of the square root of `n`, or None if `n` is negative.
+pub fn isqrt<T: Int + NumCast>(n: T) -> Option<T> {
+    if n.is_negative() {
+        return None;
+    }
+    if n < T::from(2).unwrap() {
+        return Some(n);
+    }
+    let two = T::one() + T::one();
+    // Start from a floating point estimate. One Newton step from any positive value lands on or
+    // above the true root, and from there the steps decrease monotonically onto it.
+    let estimate = n
+        .to_f64()
+        .and_then(|f| T::from(f.sqrt()))
+        .filter(|e| e.is_positive())
+        .unwrap_or(T::one());
+    let mut x = estimate / two + (n / estimate) / two + T::one();
+    loop {
+        let y = (x + n / x) / two;
+        if y >= x {
+            return Some(x);
+        }
+        x = y;
+    }
+}
+
+/// The integers `x` where `a * x^2 + b * x + c < 0`, for `a > 0`.
+/// Returns `Ok(None)` if there are none, or an error if `a` isn't positive or the calculation
+/// overflows `T`.
+pub fn quadratic_below_zero<T: Int + NumCast>(
+    a: T,
+    b: T,
+    c: T,
+) -> Result<Option<RangeInclusive<T>>> {
+    let overflow = || {
+        eyre!(
+            "Overflow solving {:?}x^2 + {:?}x + {:?} < 0",
+            a.to_i128(),
+            b.to_i128(),
+            c.to_i128()
+        )
+    };
+    if !a.is_positive() {
+        return Err(eyre!(
+            "Leading coefficient must be positive, got {:?}",
+            a.to_i128()
+        ));
+    }
+    let four = T::from(4).unwrap();
+    let discriminant = b
+        .checked_mul(&b)
+        .zip(four.checked_mul(&a).and_then(|a4| a4.checked_mul(&c)))
+        .and_then(|(b2, ac4)| b2.checked_sub(&ac4))
+        .ok_or_else(overflow)?;
+    if !discriminant.is_positive() {
+        return Ok(None);
+    }
+    let below = |x: T| -> Result<bool> {
+        a.checked_mul(&x)
+            .and_then(|ax| ax.checked_add(&b))
+            .and_then(|axb| axb.checked_mul(&x))
+            .and_then(|axbx| axbx.checked_add(&c))
+            .map(|f| f.is_negative())
+            .ok_or_else(overflow)
+    };
+    // Estimate the roots, then nudge them onto the exact integer bounds.
+    let root = isqrt(discriminant).unwrap();
+    let two_a = a + a;
+    let mut low = (-b - root).div_floor(&two_a);
+    let mut high = (-b + root).div_floor(&two_a) + T::one();
+    while low <= high && !below(low)? {
+        low = low + T::one();
+    }
+    if low > high {
+        return Ok(None);
+    }
+    while below(low - T::one())? {
+        low = low - T::one();
+    }
+    while !below(high)? {
+        high = high - T::one();
+    }
+    while below(high + T::one())? {
+        high = high + T::one();
+    }
+    Ok(Some(low..=high))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd_all([12i64, 18, 27]), 3);
+        assert_eq!(gcd_all(Vec::<i64>::new()), 0);
+        assert_eq!(lcm_all([4i64, 6, 10]), Some(60));
+        assert_eq!(lcm_all([i64::MAX, 2]), None);
+        assert_eq!(lcm_all([i64::MAX as i128, 2]), Some(2 * i64::MAX as i128));
+    }
+
+    #[test]
+    fn test_ext_gcd() {
+        let (g, x, y) = ext_gcd(240i64, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+        let (g, x, y) = ext_gcd(-15i64, 10);
+        assert_eq!(g, 5);
+        assert_eq!(-15 * x + 10 * y, 5);
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mod_pow(4i64, 13, 497), Some(445));
+        assert_eq!(mod_pow(2i64, 100, 1_000_000_007), Some(976_371_285));
+        // Needs a 128-bit intermediate product for i64
+        let m = 9_223_372_036_854_775_783i64;
+        assert_eq!(mul_mod(m - 1, m - 1, m), Some(1));
+        assert_eq!(mod_inv(3i64, 11), Some(4));
+        assert_eq!(mod_inv(6i64, 9), None);
+        assert_eq!(mod_inv(-3i64, 11), Some(7));
+    }
+
+    #[test]
+    fn test_invalid_modulus() {
+        for m in [0i64, -7] {
+            assert_eq!(mul_mod(3, 4, m), None);
+            assert_eq!(mod_pow(3, 4, m), None);
+            assert_eq!(mod_inv(3, m), None);
+            assert_eq!(crt([(0, m)]), None);
+            assert_eq!(crt([(2, 3), (1, m)]), None);
+        }
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt([(2i64, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // Non-coprime moduli
+        assert_eq!(crt([(2i64, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt([(1i64, 4), (2, 6)]), None);
+        assert_eq!(crt(Vec::<(i64, i64)>::new()), Some((0, 1)));
+        assert_eq!(crt([(1i64, i64::MAX), (0, 2)]), None);
+        assert_eq!(
+            crt([(1i128, i64::MAX as i128), (0, 2)]),
+            Some((i64::MAX as i128 + 1, 2 * i64::MAX as i128))
+        );
+    }
+
+    #[test]
+    fn test_isqrt() {
+        assert_eq!(isqrt(-1i64), None);
+        assert_eq!(isqrt(0i64), Some(0));
+        assert_eq!(isqrt(15i64), Some(3));
+        assert_eq!(isqrt(16i64), Some(4));
+        assert_eq!(isqrt(i64::MAX), Some(3_037_000_499));
+        let root = 13_043_817_825_332_782_212i128;
+        assert_eq!(isqrt(root * root), Some(root));
+        assert_eq!(isqrt(root * root - 1), Some(root - 1));
+        assert_eq!(isqrt(i128::MAX), Some(13_043_817_825_332_782_212));
+    }
+
+    #[test]
+    fn test_quadratic_below_zero() {
+        // x^2 - 7x + 9 < 0
+        assert_eq!(quadratic_below_zero(1i64, -7, 9).unwrap(), Some(2..=5));
+        // Roots exactly on integers are excluded: x^2 - 30x + 200 = (x - 10)(x - 20)
+        assert_eq!(quadratic_below_zero(1i64, -30, 200).unwrap(), Some(11..=19));
+        assert_eq!(quadratic_below_zero(1i64, 0, 1).unwrap(), None);
+        assert_eq!(quadratic_below_zero(4i64, -4, 0).unwrap(), None);
+        assert!(quadratic_below_zero(0i64, 1, 1).is_err());
+        assert!(quadratic_below_zero(1i64, i64::MAX, 1).is_err());
+    }
+}
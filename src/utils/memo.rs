@@ -0,0 +1,118 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A function that can be memoized: it takes a callback to recurse with, and an argument.
+pub trait MemoFn<K, V>: Fn(&mut dyn FnMut(K) -> V, K) -> V {}
+
+impl<K, V, F: Fn(&mut dyn FnMut(K) -> V, K) -> V> MemoFn<K, V> for F {}
+
+/// A function whose results are cached by argument.
+/// The function is given a callback it can use to recurse through the cache, so recursive
+/// solutions only need to be written once, e.g.
+/// `Memo::new(|fib, n: u64| if n < 2 { n } else { fib(n - 1) + fib(n - 2) })`.
+pub struct Memo<K, V, F> {
+    cache: HashMap<K, V>,
+    f: F,
+}
+
+impl<K, V, F> Memo<K, V, F>
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    pub fn new(f: F) -> Self {
+        Self {
+            cache: HashMap::new(),
+            f,
+        }
+    }
+
+    pub fn get(&mut self, key: K) -> V {
+        let Self { cache, f } = self;
+        call(f, cache, key)
+    }
+
+    /// Number of distinct arguments computed so far.
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    #[allow(dead_code)]
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+}
+
+fn call<K, V, F>(f: &F, cache: &mut HashMap<K, V>, key: K) -> V
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    if let Some(value) = cache.get(&key) {
+        return value.clone();
+    }
+    let value = f(&mut |k| call(f, cache, k), key.clone());
+    cache.insert(key, value.clone());
+    value
+}
+
+/// Wrap `f` in a cache, returning a plain closure. See [`Memo`].
+#[allow(dead_code)]
+pub fn memoize<K, V, F>(f: F) -> impl FnMut(K) -> V
+where
+    K: Clone + Eq + Hash,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    let mut memo = Memo::new(f);
+    move |key| memo.get(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn test_fibonacci() {
+        let calls = Cell::new(0);
+        let mut fib = Memo::new(|fib, n: u64| {
+            calls.set(calls.get() + 1);
+            if n < 2 {
+                n as u128
+            } else {
+                fib(n - 1) + fib(n - 2)
+            }
+        });
+        assert_eq!(fib.get(150), 9_969_216_677_189_303_386_214_405_760_200);
+        assert_eq!(calls.get(), 151);
+        assert_eq!(fib.len(), 151);
+        // Already cached
+        assert_eq!(fib.get(10), 55);
+        assert_eq!(calls.get(), 151);
+    }
+
+    #[test]
+    fn test_memoize_with_captured_input() {
+        // Number of ways to climb `steps` using the allowed stride lengths.
+        let strides = [1, 3, 5];
+        let mut ways = memoize(|ways, steps: i64| -> u64 {
+            match steps {
+                0 => 1,
+                s if s < 0 => 0,
+                s => strides.iter().map(|stride| ways(s - stride)).sum(),
+            }
+        });
+        assert_eq!(ways(0), 1);
+        assert_eq!(ways(4), 3);
+        assert_eq!(ways(60), 294_823_913_505);
+    }
+}
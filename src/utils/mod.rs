@@ -0,0 +1,8 @@
+// Shared helpers for solvers.
+pub mod cycle;
+pub mod geometry;
+pub mod interval;
+pub mod math;
+pub mod memo;
+pub mod parse;
+pub mod search;
@@ -0,0 +1,123 @@
+use color_eyre::eyre::{eyre, Context, Result};
+
+/// Compile a regex the first time this expression is evaluated, and reuse it afterwards.
+macro_rules! regex {
+    ($re:literal) => {{
+        static RE: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        RE.get_or_init(|| regex::Regex::new($re).unwrap())
+    }};
+}
+pub(crate) use regex;
+
+/// Every signed integer in `input`, ignoring anything between them.
+pub fn ints(input: &str) -> Result<Vec<i64>> {
+    regex!(r"-?\d+")
+        .find_iter(input)
+        .map(|m| {
+            m.as_str()
+                .parse()
+                .wrap_err_with(|| format!("Invalid integer {:?}", m.as_str()))
+        })
+        .collect()
+}
+
+/// Split `input` on `separator` into exactly `N` parts.
+pub fn split_n<'a, const N: usize>(input: &'a str, separator: &str) -> Result<[&'a str; N]> {
+    let parts: Vec<&str> = input.splitn(N, separator).collect();
+    let found = parts.len();
+    parts.try_into().map_err(|_| {
+        eyre!(
+            "Expected {} parts separated by {:?} in {:?}, found {}",
+            N,
+            separator,
+            input,
+            found
+        )
+    })
+}
+
+/// A block of the input introduced by a `label:` line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    pub label: &'a str,
+    /// Everything after the colon, including the following lines of the block.
+    pub body: &'a str,
+}
+
+/// Split `input` into blank line separated blocks, each starting with `label:`.
+pub fn sections(input: &str) -> Result<Vec<Section<'_>>> {
+    input
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|block| {
+            let [label, body] = split_n(block, ":")?;
+            Ok(Section {
+                label: label.trim(),
+                body: body.trim(),
+            })
+        })
+        .collect()
+}
+
+/// Parse a `key = (a, b)` line into its three parts.
+pub fn key_pair(input: &str) -> Result<(&str, &str, &str)> {
+    let captures = regex!(r"^\s*(\w+)\s*=\s*\(\s*(\w+)\s*,\s*(\w+)\s*\)\s*$")
+        .captures(input)
+        .ok_or_else(|| eyre!("Expected `key = (a, b)`, got {:?}", input))?;
+    Ok((
+        captures.get(1).unwrap().as_str(),
+        captures.get(2).unwrap().as_str(),
+        captures.get(3).unwrap().as_str(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(
+            ints("Card  3: 1 21 -53 | 7").unwrap(),
+            vec![3, 1, 21, -53, 7]
+        );
+        assert_eq!(ints("x=-1,y=2").unwrap(), vec![-1, 2]);
+        assert_eq!(ints("no numbers").unwrap(), vec![]);
+        assert!(ints("99999999999999999999").is_err());
+    }
+
+    #[test]
+    fn test_split_n() {
+        let [a, b] = split_n("Game 1: 3 blue: 4 red", ": ").unwrap();
+        assert_eq!((a, b), ("Game 1", "3 blue: 4 red"));
+        assert!(split_n::<3>("a | b", " | ").is_err());
+    }
+
+    #[test]
+    fn test_sections() {
+        let input = "seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n52 50 48\n";
+        let sections = sections(input).unwrap();
+        assert_eq!(
+            sections,
+            vec![
+                Section {
+                    label: "seeds",
+                    body: "79 14"
+                },
+                Section {
+                    label: "seed-to-soil map",
+                    body: "50 98 2\n52 50 48"
+                },
+            ]
+        );
+        assert!(super::sections("no label").is_err());
+    }
+
+    #[test]
+    fn test_key_pair() {
+        assert_eq!(key_pair("AAA = (BBB, CCC)").unwrap(), ("AAA", "BBB", "CCC"));
+        assert_eq!(key_pair("node1=(a,b)").unwrap(), ("node1", "a", "b"));
+        assert!(key_pair("AAA = BBB, CCC").is_err());
+    }
+}
@@ -0,0 +1,344 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+use num::Zero;
+
+/// What a search should do after visiting a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum Control {
+    /// Keep searching, expanding the neighbours of this node.
+    Continue,
+    /// Keep searching, but don't expand the neighbours of this node.
+    Prune,
+    /// Stop the search immediately.
+    Stop,
+}
+
+/// A path found by a search, from the start node to the goal node (both inclusive).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(dead_code)]
+pub struct Path<N, C> {
+    pub nodes: Vec<N>,
+    pub cost: C,
+}
+
+impl<N, C> Path<N, C> {
+    #[allow(dead_code)]
+    pub fn goal(&self) -> &N {
+        self.nodes.last().unwrap()
+    }
+}
+
+/// Every node reached by a search, with its best known cost and the node it was reached from.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct Reached<N, C> {
+    pub costs: HashMap<N, C>,
+    parents: HashMap<N, N>,
+}
+
+impl<N: Clone + Eq + Hash, C: Copy> Reached<N, C> {
+    #[allow(dead_code)]
+    pub fn cost(&self, node: &N) -> Option<C> {
+        self.costs.get(node).copied()
+    }
+
+    /// Reconstruct the path from the start node to `node`, if it was reached.
+    #[allow(dead_code)]
+    pub fn path_to(&self, node: &N) -> Option<Path<N, C>> {
+        let cost = self.cost(node)?;
+        let mut nodes = vec![node.clone()];
+        let mut current = node;
+        while let Some(parent) = self.parents.get(current) {
+            nodes.push(parent.clone());
+            current = parent;
+        }
+        nodes.reverse();
+        Some(Path { nodes, cost })
+    }
+}
+
+/// Breadth-first search for the shortest (fewest steps) path to a node matching `is_goal`.
+#[allow(dead_code)]
+pub fn bfs<N, FN, IN, FG>(start: N, neighbours: FN, mut is_goal: FG) -> Option<Path<N, usize>>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FG: FnMut(&N) -> bool,
+{
+    let mut goal = None;
+    let reached = bfs_visit(start, neighbours, |node, _| {
+        if is_goal(node) {
+            goal = Some(node.clone());
+            Control::Stop
+        } else {
+            Control::Continue
+        }
+    });
+    goal.and_then(|goal| reached.path_to(&goal))
+}
+
+/// Breadth-first search calling `visit` on every node in order of distance from `start`.
+#[allow(dead_code)]
+pub fn bfs_visit<N, FN, IN, FV>(start: N, mut neighbours: FN, mut visit: FV) -> Reached<N, usize>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FV: FnMut(&N, usize) -> Control,
+{
+    let mut reached = Reached {
+        costs: HashMap::from([(start.clone(), 0)]),
+        parents: HashMap::new(),
+    };
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((node, steps)) = queue.pop_front() {
+        match visit(&node, steps) {
+            Control::Continue => {}
+            Control::Prune => continue,
+            Control::Stop => break,
+        }
+        for next in neighbours(&node) {
+            if !reached.costs.contains_key(&next) {
+                reached.costs.insert(next.clone(), steps + 1);
+                reached.parents.insert(next.clone(), node.clone());
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+    reached
+}
+
+/// Dijkstra's algorithm for the cheapest path to a node matching `is_goal`.
+#[allow(dead_code)]
+pub fn dijkstra<N, C, FN, IN, FG>(start: N, neighbours: FN, is_goal: FG) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash + Ord,
+    C: Copy + Ord + Zero,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FG: FnMut(&N) -> bool,
+{
+    astar(start, neighbours, |_| C::zero(), is_goal)
+}
+
+/// Dijkstra's algorithm calling `visit` on every node as its cheapest cost becomes final.
+#[allow(dead_code)]
+pub fn dijkstra_visit<N, C, FN, IN, FV>(start: N, neighbours: FN, visit: FV) -> Reached<N, C>
+where
+    N: Clone + Eq + Hash + Ord,
+    C: Copy + Ord + Zero,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FV: FnMut(&N, C) -> Control,
+{
+    astar_visit(start, neighbours, |_| C::zero(), visit)
+}
+
+/// A* search for the cheapest path to a node matching `is_goal`.
+/// `heuristic` must never overestimate the remaining cost, otherwise the path found may not be the
+/// cheapest.
+#[allow(dead_code)]
+pub fn astar<N, C, FN, IN, FH, FG>(
+    start: N,
+    neighbours: FN,
+    heuristic: FH,
+    mut is_goal: FG,
+) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash + Ord,
+    C: Copy + Ord + Zero,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FG: FnMut(&N) -> bool,
+{
+    let mut goal = None;
+    let reached = astar_visit(start, neighbours, heuristic, |node, _| {
+        if is_goal(node) {
+            goal = Some(node.clone());
+            Control::Stop
+        } else {
+            Control::Continue
+        }
+    });
+    goal.and_then(|goal| reached.path_to(&goal))
+}
+
+/// A* search calling `visit` on every node as it is taken off the open set.
+#[allow(dead_code)]
+pub fn astar_visit<N, C, FN, IN, FH, FV>(
+    start: N,
+    mut neighbours: FN,
+    mut heuristic: FH,
+    mut visit: FV,
+) -> Reached<N, C>
+where
+    N: Clone + Eq + Hash + Ord,
+    C: Copy + Ord + Zero,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FV: FnMut(&N, C) -> Control,
+{
+    let mut reached = Reached {
+        costs: HashMap::from([(start.clone(), C::zero())]),
+        parents: HashMap::new(),
+    };
+    let mut open = BinaryHeap::new();
+    open.push(Reverse((heuristic(&start), C::zero(), start)));
+    while let Some(Reverse((_, cost, node))) = open.pop() {
+        // Skip stale entries left behind when a cheaper route to the node was found.
+        if reached.costs.get(&node).is_some_and(|best| cost > *best) {
+            continue;
+        }
+        match visit(&node, cost) {
+            Control::Continue => {}
+            Control::Prune => continue,
+            Control::Stop => break,
+        }
+        for (next, step_cost) in neighbours(&node) {
+            let next_cost = cost + step_cost;
+            if reached
+                .costs
+                .get(&next)
+                .is_none_or(|best| next_cost < *best)
+            {
+                reached.costs.insert(next.clone(), next_cost);
+                reached.parents.insert(next.clone(), node.clone());
+                open.push(Reverse((next_cost + heuristic(&next), next_cost, next)));
+            }
+        }
+    }
+    reached
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAZE: &str = r#"S.#.....
+.##.###.
+....#...
+.##...#G
+"#;
+
+    fn grid() -> Vec<Vec<char>> {
+        MAZE.lines().map(|s| s.chars().collect()).collect()
+    }
+
+    fn open_neighbours(grid: &[Vec<char>], (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        let mut out = vec![];
+        if x > 0 {
+            out.push((x - 1, y));
+        }
+        if y > 0 {
+            out.push((x, y - 1));
+        }
+        if x + 1 < grid[0].len() {
+            out.push((x + 1, y));
+        }
+        if y + 1 < grid.len() {
+            out.push((x, y + 1));
+        }
+        out.retain(|&(x, y)| grid[y][x] != '#');
+        out
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = grid();
+        let path = bfs(
+            (0, 0),
+            |&p| open_neighbours(&grid, p),
+            |&(x, y)| grid[y][x] == 'G',
+        )
+        .unwrap();
+        assert_eq!(path.cost, 12);
+        assert_eq!(path.nodes.len(), 13);
+        assert_eq!(path.nodes[0], (0, 0));
+        assert_eq!(*path.goal(), (7, 3));
+        // Every step of the reconstructed path moves to an adjacent tile
+        assert!(path
+            .nodes
+            .windows(2)
+            .all(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1) == 1));
+    }
+
+    #[test]
+    fn test_bfs_unreachable() {
+        let grid = grid();
+        assert_eq!(bfs((0, 0), |&p| open_neighbours(&grid, p), |_| false), None);
+    }
+
+    #[test]
+    fn test_bfs_visit_prune() {
+        let grid = grid();
+        let mut visited = 0;
+        let reached = bfs_visit(
+            (0, 0),
+            |&p| open_neighbours(&grid, p),
+            |_, steps| {
+                visited += 1;
+                if steps >= 2 {
+                    Control::Prune
+                } else {
+                    Control::Continue
+                }
+            },
+        );
+        assert_eq!(visited, 4);
+        assert_eq!(reached.cost(&(0, 2)), Some(2));
+        assert_eq!(reached.cost(&(0, 3)), None);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // Tile cost is the digit on the tile being entered
+        let grid: Vec<Vec<u32>> = ["1163", "1381", "2136"]
+            .iter()
+            .map(|s| s.chars().map(|c| c.to_digit(10).unwrap()).collect())
+            .collect();
+        let neighbours = |&(x, y): &(usize, usize)| {
+            [
+                (x + 1, y),
+                (x, y + 1),
+                (x.wrapping_sub(1), y),
+                (x, y.wrapping_sub(1)),
+            ]
+            .into_iter()
+            .filter(|&(x, y)| x < 4 && y < 3)
+            .map(|(x, y)| ((x, y), grid[y][x]))
+            .collect::<Vec<_>>()
+        };
+        let path = dijkstra((0, 0), neighbours, |&p| p == (3, 2)).unwrap();
+        assert_eq!(path.cost, 13);
+        assert_eq!(
+            path.nodes,
+            vec![(0, 0), (0, 1), (0, 2), (1, 2), (2, 2), (3, 2)]
+        );
+
+        let reached = dijkstra_visit((0, 0), neighbours, |_, _| Control::Continue);
+        assert_eq!(reached.cost(&(3, 0)), Some(10));
+    }
+
+    #[test]
+    fn test_astar() {
+        let grid = grid();
+        let goal = (7usize, 3usize);
+        let path = astar(
+            (0, 0),
+            |&p| open_neighbours(&grid, p).into_iter().map(|n| (n, 1)),
+            |&(x, y)| x.abs_diff(goal.0) + y.abs_diff(goal.1),
+            |&p| p == goal,
+        )
+        .unwrap();
+        assert_eq!(path.cost, 12);
+        assert_eq!(*path.goal(), goal);
+    }
+}